@@ -0,0 +1,72 @@
+//! Timings of queries against `data/test1`; run by `cargo bench`
+//! (with `--features mmap` to include [kmc_rs::KmcMmap]).
+use kmc_rs::{KmcError, KmcFile, Kmer};
+use std::time::Instant;
+
+/// [KmcFile::count_kmer] in a loop versus [KmcFile::count_kmers].
+fn count_kmers() -> Result<(), KmcError> {
+    let io = KmcFile::open_ra("./data/test1")?;
+    let kmers: Vec<Kmer> = (0..100_000).map(|i| Kmer::from_u64(5, i % 1024)).collect();
+
+    let start = Instant::now();
+    let naive: Vec<usize> = kmers.iter().map(|kmer| io.count_kmer(kmer)).collect();
+    let naive_time = start.elapsed();
+
+    let start = Instant::now();
+    let batch = io.count_kmers(&kmers);
+    let batch_time = start.elapsed();
+
+    assert!(naive == batch);
+    println!(
+        "count_kmer loop: {:?}, count_kmers: {:?}",
+        naive_time, batch_time
+    );
+    Ok(())
+}
+
+/// [KmcFile::kmer_length], read once when opening, versus a call into KMC.
+fn kmer_length() -> Result<(), KmcError> {
+    let io = KmcFile::open_iter("./data/test1")?;
+    let start = Instant::now();
+    let ffi: u64 = (0..1_000_000).map(|_| io.lut_prefix_length() as u64).sum();
+    let ffi_time = start.elapsed();
+
+    let start = Instant::now();
+    let cached: u64 = (0..1_000_000).map(|_| io.kmer_length() as u64).sum();
+    let cached_time = start.elapsed();
+
+    assert!(ffi > 0 && cached > 0);
+    println!(
+        "FFI lut_prefix_length: {:?}, kmer_length: {:?}",
+        ffi_time, cached_time
+    );
+    Ok(())
+}
+
+/// [KmcFile::open_ra] versus [kmc_rs::KmcMmap], including opening.
+#[cfg(feature = "mmap")]
+fn open_ra_mmap() -> Result<(), KmcError> {
+    let kmers: Vec<Kmer> = (0..100_000).map(|i| Kmer::from_u64(5, i % 1024)).collect();
+
+    let start = Instant::now();
+    let io = KmcFile::open_ra("./data/test1")?;
+    let ra: Vec<usize> = kmers.iter().map(|kmer| io.count_kmer(kmer)).collect();
+    let ra_time = start.elapsed();
+
+    let start = Instant::now();
+    let db = kmc_rs::KmcMmap::open("./data/test1")?;
+    let mmap: Vec<usize> = kmers.iter().map(|kmer| db.count_kmer(kmer)).collect();
+    let mmap_time = start.elapsed();
+
+    assert!(ra == mmap);
+    println!("open_ra: {:?}, KmcMmap: {:?}", ra_time, mmap_time);
+    Ok(())
+}
+
+fn main() -> Result<(), KmcError> {
+    count_kmers()?;
+    kmer_length()?;
+    #[cfg(feature = "mmap")]
+    open_ra_mmap()?;
+    Ok(())
+}
@@ -1,8 +1,39 @@
+use std::env;
+use std::path::PathBuf;
+
 fn main() {
-    cxx_build::bridge("src/cxxbridge.rs")
-        .file("KMC/kmc_api/kmc_file.cpp")
-        .file("KMC/kmc_api/mmer.cpp")
-        .file("KMC/kmc_api/kmer_api.cpp")
-        .flag_if_supported("-std=c++17")
-        .compile("kmc-rs");
+    let mut build = cxx_build::bridge("src/cxxbridge.rs");
+
+    // feature `static-kmc`: where the KMC API comes from
+    let headers = if env::var_os("CARGO_FEATURE_STATIC_KMC").is_some() {
+        build
+            .file("KMC/kmc_api/kmc_file.cpp")
+            .file("KMC/kmc_api/mmer.cpp")
+            .file("KMC/kmc_api/kmer_api.cpp");
+        PathBuf::from("KMC/kmc_api")
+    } else {
+        println!("cargo:rerun-if-env-changed=KMC_LIB_DIR");
+        println!("cargo:rerun-if-env-changed=KMC_INCLUDE_DIR");
+        if let Some(dir) = env::var_os("KMC_LIB_DIR") {
+            println!("cargo:rustc-link-search=native={}", dir.to_string_lossy());
+        }
+        println!("cargo:rustc-link-lib=dylib=kmc_api");
+        env::var_os("KMC_INCLUDE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("KMC/kmc_api"))
+    };
+    build.include(headers);
+
+    // feature `cpp17`: whether C++17 is required or only used if available
+    let flag = if build.get_compiler().is_like_msvc() {
+        "/std:c++17"
+    } else {
+        "-std=c++17"
+    };
+    if env::var_os("CARGO_FEATURE_CPP17").is_some() {
+        build.flag(flag);
+    } else {
+        build.flag_if_supported(flag);
+    }
+    build.compile("kmc-rs");
 }
@@ -0,0 +1,261 @@
+//! Set operations on data bases like `kmc_tools simple`.
+//!
+//! KMC's API can only read data bases (`kmc_tools` is a program, not part of
+//! the API), so unlike the rest of this crate these are implemented in Rust:
+//! both input data bases are listed side by side (they are sorted), so this
+//! needs little memory.
+//! Data bases of KMC 2 are listed bin by bin, so they are rejected with
+//! [KmcError::Unsorted].
+//!
+//! The result is written by a [KmcWriter] with the smaller of the minimal
+//! counts, the larger of the maximal counts and the larger counter size of
+//! the inputs; combined counts beyond these are cut off.
+//! ```rust
+//! use kmc_rs::complex::{intersect, CountOp};
+//!
+//! # struct Cleanup(std::path::PathBuf);
+//! # impl Drop for Cleanup {
+//! #     fn drop(&mut self) { let _ = std::fs::remove_dir_all(&self.0); }
+//! # }
+//! # let dir = std::env::temp_dir().join(format!("kmc-rs-complex-{}", std::process::id()));
+//! # std::fs::create_dir_all(&dir)?;
+//! # let _cleanup = Cleanup(dir.clone());
+//! intersect("data/test1", "data/test1", dir.join("both"), CountOp::Sum)?;
+//! let db = kmc_rs::KmcFile::open_ra(dir.join("both"))?;
+//! assert_eq!(db.count_str("TAAGA")?, 8);
+//! # Ok::<(), kmc_rs::KmcError>(())
+//! ```
+use crate::{KmcError, KmcFile, KmcWriter};
+use std::path::Path;
+
+/// How to combine the counts of a kmer occurring in both data bases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountOp {
+    /// The smaller count.
+    Min,
+    /// The larger count.
+    Max,
+    /// The sum of both counts (cut off at the largest count of the output).
+    Sum,
+    /// The count of the first data base.
+    Left,
+    /// The count of the second data base.
+    Right,
+}
+
+impl CountOp {
+    fn apply(self, a: usize, b: usize) -> usize {
+        match self {
+            CountOp::Min => a.min(b),
+            CountOp::Max => a.max(b),
+            CountOp::Sum => a.saturating_add(b),
+            CountOp::Left => a,
+            CountOp::Right => b,
+        }
+    }
+}
+
+/// Write the kmers of `a` or `b` to `out`; kmers in both get the count `op`.
+pub fn union(
+    a: impl AsRef<Path>,
+    b: impl AsRef<Path>,
+    out: impl AsRef<Path>,
+    op: CountOp,
+) -> Result<(), KmcError> {
+    merge(a.as_ref(), b.as_ref(), out.as_ref(), |ca, cb| {
+        match (ca, cb) {
+            (Some(ca), Some(cb)) => Some(op.apply(ca, cb)),
+            (ca, cb) => ca.or(cb),
+        }
+    })
+}
+
+/// Write the kmers of both `a` and `b` to `out` with count `op`.
+pub fn intersect(
+    a: impl AsRef<Path>,
+    b: impl AsRef<Path>,
+    out: impl AsRef<Path>,
+    op: CountOp,
+) -> Result<(), KmcError> {
+    merge(a.as_ref(), b.as_ref(), out.as_ref(), |ca, cb| {
+        Some(op.apply(ca?, cb?))
+    })
+}
+
+/// Write the kmers of `a` that are not in `b` to `out`, keeping the counts of `a`.
+pub fn subtract(
+    a: impl AsRef<Path>,
+    b: impl AsRef<Path>,
+    out: impl AsRef<Path>,
+) -> Result<(), KmcError> {
+    merge(a.as_ref(), b.as_ref(), out.as_ref(), |ca, cb| match cb {
+        Some(_) => None,
+        None => ca,
+    })
+}
+
+/// List `a` and `b` side by side (see [KmcFile::merge_with]) and write every kmer to `out` for which
+/// `combine` of its counts in `a` and `b` returns a count.
+fn merge<F>(a: &Path, b: &Path, out: &Path, mut combine: F) -> Result<(), KmcError>
+where
+    F: FnMut(Option<usize>, Option<usize>) -> Option<usize>,
+{
+    let mut a = KmcFile::open_iter(a)?;
+    let mut b = KmcFile::open_iter(b)?;
+    if a.kmer_length() != b.kmer_length() {
+        return Err(KmcError::LengthMismatch {
+            expected: a.kmer_length(),
+            got: b.kmer_length(),
+        });
+    }
+    if a.both_strands() != b.both_strands() {
+        return Err(KmcError::Format(
+            "cannot combine data bases counting one and both strands".to_string(),
+        ));
+    }
+    a.check_sorted()?;
+    b.check_sorted()?;
+    let max_count = a.max_count().max(b.max_count()).min(u32::MAX as u64) as u32;
+    let mut writer = KmcWriter::create(out, a.kmer_length())?
+        .counter_size(a.counter_size().max(b.counter_size()))
+        .count_range(a.min_count().min(b.min_count()), max_count);
+    if !a.both_strands() {
+        writer = writer.single_strand();
+    }
+    let max_count = max_count.min(writer.max_counter()) as usize;
+    for (kmer, ca, cb) in a.merge_with(&mut b)? {
+        if let Some(count) = combine(ca, cb) {
+            writer.add(&kmer, count.min(max_count) as u32)?;
+        }
+    }
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_kmc2, TempDir};
+    use crate::Kmer;
+
+    fn listing(path: impl AsRef<Path>) -> Result<Vec<(String, usize)>, KmcError> {
+        Ok(KmcFile::open_iter(path)?.iter_strings().collect())
+    }
+
+    #[test]
+    fn test_intersect_self() -> Result<(), KmcError> {
+        let dir = TempDir::new("intersect-self")?;
+        intersect(
+            "./data/test1",
+            "./data/test1",
+            dir.join("out"),
+            CountOp::Min,
+        )?;
+        let result = listing(dir.join("out"));
+        assert_eq!(result?, listing("./data/test1")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_operations() -> Result<(), KmcError> {
+        let dir = TempDir::new("set-operations")?;
+        let mut writer = KmcWriter::create(dir.join("small"), 5)?;
+        writer.add(&Kmer::from("AAAAA")?, 7)?;
+        writer.add(&Kmer::from("AAAAG")?, 1)?;
+        writer.add(&Kmer::from("TAAGA")?, 1)?;
+        writer.finish()?;
+
+        union(
+            "./data/test1",
+            dir.join("small"),
+            dir.join("union"),
+            CountOp::Max,
+        )?;
+        intersect(
+            "./data/test1",
+            dir.join("small"),
+            dir.join("inter"),
+            CountOp::Sum,
+        )?;
+        subtract("./data/test1", dir.join("small"), dir.join("sub"))?;
+        subtract("./data/test1", "./data/test1", dir.join("empty"))?;
+        let union = KmcFile::open_ra(dir.join("union"))?;
+        let inter = listing(dir.join("inter"))?;
+        let sub = KmcFile::open_ra(dir.join("sub"))?;
+        let empty = KmcFile::open_ra(dir.join("empty"))?;
+
+        assert_eq!(union.total_kmers(), 292);
+        assert_eq!(union.count_str("AAAAA")?, 7);
+        assert_eq!(union.count_str("AAAAG")?, 1);
+        assert_eq!(union.count_str("TAAGA")?, 4);
+        assert_eq!(
+            inter,
+            vec![("AAAAA".to_string(), 9), ("TAAGA".to_string(), 5)]
+        );
+        assert_eq!(sub.total_kmers(), 289);
+        assert_eq!(sub.count_str("TAAGA")?, 0);
+        assert_eq!(sub.count_str("AAAAC")?, 2);
+        assert_eq!(empty.total_kmers(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_length_mismatch() -> Result<(), KmcError> {
+        let dir = TempDir::new("complex-mismatch")?;
+        let mut writer = KmcWriter::create(dir.join("k4"), 4)?;
+        writer.add(&Kmer::from("AAAA")?, 1)?;
+        writer.finish()?;
+        let result = union(
+            "./data/test1",
+            dir.join("k4"),
+            dir.join("out"),
+            CountOp::Sum,
+        );
+        assert!(matches!(
+            result,
+            Err(KmcError::LengthMismatch {
+                expected: 5,
+                got: 4
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_header() -> Result<(), KmcError> {
+        let dir = TempDir::new("complex-header")?;
+        intersect(
+            "./data/test1",
+            "./data/test1",
+            dir.join("sum"),
+            CountOp::Sum,
+        )?;
+        let sum = KmcFile::open_ra(dir.join("sum"))?;
+        assert_eq!(
+            (sum.counter_size(), sum.min_count(), sum.max_count()),
+            (1, 2, 1_000_000_000)
+        );
+        assert_eq!(sum.count_str("TAAGA")?, 8);
+
+        let mut writer = KmcWriter::create(dir.join("one-byte"), 5)?.counter_size(1);
+        writer.add(&Kmer::from("TAAGA")?, 200)?;
+        writer.finish()?;
+        union(
+            dir.join("one-byte"),
+            dir.join("one-byte"),
+            dir.join("cut"),
+            CountOp::Sum,
+        )?;
+        assert_eq!(KmcFile::open_ra(dir.join("cut"))?.count_str("TAAGA")?, 255);
+        Ok(())
+    }
+
+    #[test]
+    fn test_kmc2() -> Result<(), KmcError> {
+        let dir = TempDir::new("complex-kmc2")?;
+        write_kmc2(&dir.join("kmc2"), 5, &[&[("TAAGA", 4)], &[("AAAAC", 2)]])?;
+        let result = subtract(dir.join("kmc2"), "./data/test1", dir.join("out"));
+        assert!(matches!(result, Err(KmcError::Unsorted { .. })));
+        assert!(!dir.join("out.kmc_suf").exists());
+        Ok(())
+    }
+}
@@ -1,12 +1,18 @@
-#include "../KMC/kmc_api/kmc_file.h"
-#include "../KMC/kmc_api/kmer_api.h"
+#include "kmc_file.h"
+#include "kmer_api.h"
 #if __has_include("rust/cxx.h")
 #    include "rust/cxx.h"
 #    define HAVE_RUST
 #else
 #    undef HAVE_RUST
 #endif
+#include <algorithm> // for std::copy_n
+#include <cerrno>
+#include <cstdio> // for std::fopen
+#include <cstring> // for std::strerror
 #include <memory> // for std::unique_ptr
+#include <stdexcept> // for std::runtime_error
+#include <vector>
 
 
 struct Kmer : public CKmerAPI
@@ -18,7 +24,15 @@ struct Kmer : public CKmerAPI
     inline uint64_t as_u64() const
     {
         const auto offset = this->kmer_length + this->byte_alignment;
-        return data0() >> (64 - (offset * 2));
+        if (offset == 0)
+            return 0;
+        if (offset <= 32)
+            return data0() >> (64 - (offset * 2));
+        // the first 32 symbols are spread over the first two rows
+        const auto shift = 2 * this->byte_alignment;
+        if (shift == 0)
+            return data0();
+        return (data0() << shift) | (this->kmer_data[1] >> (64 - shift));
     }
 
     bool set_u64(uint64_t val)
@@ -26,13 +40,26 @@ struct Kmer : public CKmerAPI
         if (this->kmer_length > 32)
             return false;
         const auto offset = this->kmer_length + this->byte_alignment;
-        this->kmer_data[0] = (uint64)val << (64 - (offset * 2));
+        if (offset > 0) // otherwise there is no row
+            this->kmer_data[0] = (uint64)val << (64 - (offset * 2));
         return true;
     }
 
     uint32_t kmer_len() const { return this->kmer_length; }
 
+    uint32_t byte_align() const { return this->byte_alignment; }
+
 #ifdef HAVE_RUST
+    rust::Slice<const uint64_t> data() const
+    {
+        return {reinterpret_cast<const uint64_t *>(this->kmer_data), this->no_of_rows};
+    }
+
+    rust::Slice<uint64_t> data_mut()
+    {
+        return {reinterpret_cast<uint64_t *>(this->kmer_data), this->no_of_rows};
+    }
+
     bool from_string(rust::Str kmer)
     {
         return CKmerAPI::from_string(std::string(kmer));
@@ -45,12 +72,51 @@ struct Kmer : public CKmerAPI
 
 struct KmcFile : public CKMCFile
 {
+    /// errno of the failed fopen of the last attempt to open (0 if both
+    /// files could be opened, i.e. KMC rejected their content)
+    int open_errno = 0;
+
+    /// Whether both files of `fname` can be opened for reading; otherwise
+    /// remember the reason. KMC itself neither reports nor sets errno for
+    /// malformed files, so errno is not trusted after it failed.
+    bool probe_files(const std::string &fname)
+    {
+        open_errno = 0;
+        for (const char *suffix : {".kmc_pre", ".kmc_suf"}) {
+            errno = 0;
+            FILE *file = std::fopen((fname + suffix).c_str(), "rb");
+            if (!file) {
+                open_errno = errno;
+                return false;
+            }
+            std::fclose(file);
+        }
+        return true;
+    }
+
 #ifdef HAVE_RUST
-    bool open_for_ra(const rust::Str fname) { return OpenForRA(std::string(fname)); }
-    bool open_for_iter(const rust::Str fn) { return OpenForListing(std::string(fn)); }
-#endif
+    /// `probe`: check the files first (unless the caller already did)
+    bool open_for_ra(const rust::Str fname, const bool probe)
+    {
+        const std::string name(fname);
+        open_errno = 0;
+        return (!probe || probe_files(name)) && OpenForRA(name);
+    }
+
+    bool open_for_iter(const rust::Str fn, const bool probe)
+    {
+        const std::string name(fn);
+        open_errno = 0;
+        return (!probe || probe_files(name)) && OpenForListing(name);
+    }
 
-    inline std::size_t kmer_count() { return KmerCount(); }
+    rust::String open_error() const
+    {
+        if (open_errno != 0)
+            return std::strerror(open_errno);
+        return "not a KMC data base";
+    }
+#endif
 
     inline bool next(Kmer &kmer, size_t &count)
     {
@@ -60,12 +126,46 @@ struct KmcFile : public CKMCFile
         return r;
     }
 
+    bool try_next(Kmer &kmer, size_t &count)
+    {
+        if (!next(kmer, count))
+            return false;
+        if (count == 0) // KMC never stores a k-mer that did not occur
+            throw std::runtime_error("zero counter");
+        return true;
+    }
+
     inline bool restart_listing() { return RestartListing(); }
 
     inline uint32_t kmer_len() const { return KmerLength(); }
 
+    inline CKMCFileInfo info() const
+    {
+        CKMCFileInfo info;
+        Info(info);
+        return info;
+    }
+
+    inline uint32_t min_count() const { return info().min_count; }
+
+    inline uint64_t max_count() const { return info().max_count; }
+
+    inline bool both_strands() const { return info().both_strands; }
+
+    inline uint32_t counter_size() const { return info().counter_size; }
+
+    inline uint32_t kmc_mode() const { return info().mode; }
+
+    inline uint32_t lut_prefix_length() const { return info().lut_prefix_length; }
+
+    inline uint32_t signature_len() const { return info().signature_len; }
+
+    inline uint64_t total_kmers() const { return info().total_kmers; }
+
     inline size_t check_kmer(const Kmer &kmer) const
     {
+        if (kmer.kmer_len() != kmer_len()) // KMC would read past the rows of kmer
+            return 0;
         uint64 counter = 0;
         if (CheckKmer(kmer, counter))
             return (size_t)counter;
@@ -73,6 +173,41 @@ struct KmcFile : public CKMCFile
     }
 
     inline bool close() { return Close(); }
+
+#ifdef HAVE_RUST
+    void check_kmers(const rust::Slice<const uint64_t> data, rust::Vec<size_t> &counts) const
+    {
+        Kmer kmer(kmer_len());
+        const size_t rows = kmer.data().size();
+        counts.reserve(data.size() / rows);
+        for (size_t i = 0; i + rows <= data.size(); i += rows) {
+            std::copy_n(data.data() + i, rows, kmer.data_mut().data());
+            counts.push_back(check_kmer(kmer));
+        }
+    }
+
+    size_t read_block(rust::Slice<uint64_t> kmers, rust::Slice<size_t> counts)
+    {
+        const size_t n = std::min(kmers.size(), counts.size());
+        Kmer kmer(kmer_len());
+        size_t i = 0;
+        for (; i < n && next(kmer, counts[i]); i++)
+            kmers[i] = kmer.as_u64();
+        return i;
+    }
+
+    bool counts_for_read(const rust::Slice<const uint8_t> read, rust::Vec<size_t> &counts) const
+    {
+        std::vector<uint32> counters;
+        const std::string seq(reinterpret_cast<const char *>(read.data()), read.size());
+        if (!GetCountersForRead(seq, counters))
+            return false;
+        counts.reserve(counters.size());
+        for (const auto c : counters)
+            counts.push_back(c);
+        return true;
+    }
+#endif
 };
 
 
@@ -6,14 +6,30 @@ pub(crate) mod ffi {
         type Kmer;
 
         fn new_ckmc_file() -> UniquePtr<KmcFile>;
-        fn open_for_ra(self: Pin<&mut KmcFile>, fname: &str) -> bool;
-        fn open_for_iter(self: Pin<&mut KmcFile>, fname: &str) -> bool;
+        fn open_for_ra(self: Pin<&mut KmcFile>, fname: &str, probe: bool) -> bool;
+        fn open_for_iter(self: Pin<&mut KmcFile>, fname: &str, probe: bool) -> bool;
+        fn open_error(self: &KmcFile) -> String;
         fn kmer_len(self: &KmcFile) -> u32;
-        fn kmer_count(self: Pin<&mut KmcFile>) -> usize;
+        fn min_count(self: &KmcFile) -> u32;
+        fn max_count(self: &KmcFile) -> u64;
+        fn both_strands(self: &KmcFile) -> bool;
+        fn counter_size(self: &KmcFile) -> u32;
+        fn kmc_mode(self: &KmcFile) -> u32;
+        fn lut_prefix_length(self: &KmcFile) -> u32;
+        fn signature_len(self: &KmcFile) -> u32;
+        fn total_kmers(self: &KmcFile) -> u64;
         fn check_kmer(self: &KmcFile, kmer: &Kmer) -> usize;
+        fn check_kmers(self: &KmcFile, data: &[u64], counts: &mut Vec<usize>);
         fn close(self: Pin<&mut KmcFile>) -> bool;
         fn next(self: Pin<&mut KmcFile>, kmer: Pin<&mut Kmer>, count: &mut usize) -> bool;
+        fn try_next(
+            self: Pin<&mut KmcFile>,
+            kmer: Pin<&mut Kmer>,
+            count: &mut usize,
+        ) -> Result<bool>;
+        fn read_block(self: Pin<&mut KmcFile>, kmers: &mut [u64], counts: &mut [usize]) -> usize;
         fn restart_listing(self: Pin<&mut KmcFile>) -> bool;
+        fn counts_for_read(self: &KmcFile, read: &[u8], counts: &mut Vec<usize>) -> bool;
 
         fn new_kmerapi() -> UniquePtr<Kmer>;
         fn new_kmerapi_with_len(k: u32) -> UniquePtr<Kmer>;
@@ -22,5 +38,8 @@ pub(crate) mod ffi {
         fn to_string(self: &Kmer) -> String;
         fn kmer_len(self: &Kmer) -> u32;
         fn as_u64(self: &Kmer) -> u64;
+        fn byte_align(self: &Kmer) -> u32;
+        fn data(self: &Kmer) -> &[u64];
+        fn data_mut(self: Pin<&mut Kmer>) -> &mut [u64];
     }
 }
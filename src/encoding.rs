@@ -0,0 +1,120 @@
+//! KMC's 2-bit coding of the symbols:
+//!
+//! | symbol | code   |
+//! |--------|--------|
+//! | `A`    | `0b00` |
+//! | `C`    | `0b01` |
+//! | `G`    | `0b10` |
+//! | `T`    | `0b11` |
+//!
+//! Hence, the order of the codes is the alphabetical order of the symbols and
+//! the complement of a code `c` is `0b11 - c`.
+
+/// The 2-bit code of the ASCII symbol `b` (upper or lower case) or `None` if
+/// `b` is not one of `ACGT`.
+/// ```rust
+/// use kmc_rs::encoding::base_to_code;
+///
+/// assert_eq!(base_to_code(b'G'), Some(0b10));
+/// assert_eq!(base_to_code(b'N'), None);
+/// ```
+pub const fn base_to_code(b: u8) -> Option<u8> {
+    match b {
+        b'A' | b'a' => Some(0b00),
+        b'C' | b'c' => Some(0b01),
+        b'G' | b'g' => Some(0b10),
+        b'T' | b't' => Some(0b11),
+        _ => None,
+    }
+}
+
+/// The (upper case) ASCII symbol of the 2-bit code `c`; higher bits are ignored.
+/// ```rust
+/// use kmc_rs::encoding::code_to_base;
+///
+/// assert_eq!(code_to_base(0b10), b'G');
+/// ```
+pub const fn code_to_base(c: u8) -> u8 {
+    b"ACGT"[(c & 0b11) as usize]
+}
+
+/// The reverse complement of the `k` symbols encoded in the lowest `2 * k` bits
+/// of `val` (layout as in [Kmer::set_u64](crate::Kmer::set_u64)).
+/// Note: `k` must be at most `32`!
+/// ```rust
+/// use kmc_rs::encoding::reverse_complement_u64;
+///
+/// // TAAGA -> TCTTA
+/// assert_eq!(reverse_complement_u64(5, 0b11_00_00_10_00), 0b11_01_11_11_00);
+/// ```
+pub fn reverse_complement_u64(k: u8, val: u64) -> u64 {
+    debug_assert!(k <= 32, "k = {} does not fit into 64 bits", k);
+    if k == 0 {
+        return 0;
+    }
+    let x = !val;
+    let x = ((x >> 2) & 0x3333_3333_3333_3333) | ((x & 0x3333_3333_3333_3333) << 2);
+    let x = ((x >> 4) & 0x0F0F_0F0F_0F0F_0F0F) | ((x & 0x0F0F_0F0F_0F0F_0F0F) << 4);
+    x.swap_bytes() >> (64 - 2 * k as u32)
+}
+
+/// The smaller of `val` and its [reverse complement](reverse_complement_u64),
+/// i.e. the canonical form of the encoded `k`-mer.
+/// Note: `k` must be at most `32`!
+/// ```rust
+/// use kmc_rs::encoding::canonical_u64;
+///
+/// // TCTTA -> TAAGA
+/// assert_eq!(canonical_u64(5, 0b11_01_11_11_00), 0b11_00_00_10_00);
+/// ```
+pub fn canonical_u64(k: u8, val: u64) -> u64 {
+    val.min(reverse_complement_u64(k, val))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for (code, base) in b"ACGT".iter().enumerate() {
+            let code = code as u8;
+            assert_eq!(base_to_code(*base), Some(code));
+            assert_eq!(base_to_code(base.to_ascii_lowercase()), Some(code));
+            assert_eq!(code_to_base(code), *base);
+        }
+        assert_eq!(code_to_base(0b111), b'T');
+    }
+
+    #[test]
+    fn test_invalid() {
+        for b in b"NnUu -\n".iter() {
+            assert_eq!(base_to_code(*b), None);
+        }
+    }
+
+    #[test]
+    fn test_reverse_complement_u64() -> Result<(), crate::KmcError> {
+        for s in ["A", "ACGT", "TAAGA", "ACCGTTTGACAGTAGCATTACGATCGACGTTA"].iter() {
+            let kmer = crate::Kmer::from(s)?;
+            assert_eq!(
+                reverse_complement_u64(kmer.len() as u8, kmer.as_u64()),
+                kmer.reverse_complement().as_u64(),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(reverse_complement_u64(0, 0), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches_kmc() -> Result<(), crate::KmcError> {
+        let kmer = crate::Kmer::from("ACGT")?;
+        for pos in 0..4 {
+            let base = kmer.base_char_at(pos).unwrap() as u8;
+            assert_eq!(base_to_code(base), kmer.base_at(pos));
+        }
+        Ok(())
+    }
+}
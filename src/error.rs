@@ -0,0 +1,96 @@
+/// The two modes a [KmcFile](crate::KmcFile) can be opened in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenMode {
+    /// See [KmcFile::open_ra](crate::KmcFile::open_ra).
+    RandomAccess,
+    /// See [KmcFile::open_iter](crate::KmcFile::open_iter).
+    Listing,
+}
+
+/// Everything that might go wrong in this crate.
+#[derive(Debug)]
+pub enum KmcError {
+    /// KMC could not open the data base `path` in `mode`; `reason` is the
+    /// message of the operating system (e.g. "No such file or directory")
+    /// if it reported one.
+    OpenFailed {
+        path: String,
+        mode: OpenMode,
+        reason: String,
+    },
+    /// One of the two files of a data base (`.kmc_pre` or `.kmc_suf`) cannot be read.
+    MissingFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    /// The path of a data base is not valid UTF-8.
+    InvalidPath(std::path::PathBuf),
+    /// Not a valid kmer, e.g. because of symbols other than `ACGT`.
+    InvalidKmer(String),
+    /// A kmer of length `got` was passed where length `expected` is needed.
+    LengthMismatch { expected: u32, got: u32 },
+    /// The operation needs a sorted listing, but the data base `path` was
+    /// written by KMC 2, which lists the kmers bin by bin.
+    Unsorted { path: String },
+    /// KMC reported a failure without a reason; the string tells what it
+    /// failed to do.
+    Kmc(String),
+    /// Reading or writing failed.
+    Io(std::io::Error),
+    /// Malformed input, e.g. a broken FASTQ record.
+    Format(String),
+}
+
+impl std::fmt::Display for OpenMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OpenMode::RandomAccess => f.write_str("random access"),
+            OpenMode::Listing => f.write_str("listing"),
+        }
+    }
+}
+
+impl std::fmt::Display for KmcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            KmcError::OpenFailed { path, mode, reason } => {
+                write!(f, "Could not open '{}' in {} mode: {}", path, mode, reason)
+            }
+            KmcError::MissingFile { path, source } => {
+                write!(f, "Cannot read '{}': {}", path.display(), source)
+            }
+            KmcError::InvalidPath(path) => {
+                write!(f, "Path is not valid UTF-8: {}", path.display())
+            }
+            KmcError::InvalidKmer(reason) => write!(f, "Invalid kmer: {}", reason),
+            KmcError::LengthMismatch { expected, got } => {
+                write!(f, "Expected a kmer of length {}, got {}", expected, got)
+            }
+            KmcError::Unsorted { path } => {
+                write!(
+                    f,
+                    "The listing of '{}' is not sorted (KMC 2 data base)",
+                    path
+                )
+            }
+            KmcError::Kmc(what) => write!(f, "KMC failed to {}", what),
+            KmcError::Io(err) => write!(f, "I/O error: {}", err),
+            KmcError::Format(reason) => write!(f, "Malformed input: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for KmcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KmcError::Io(err) | KmcError::MissingFile { source: err, .. } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for KmcError {
+    fn from(err: std::io::Error) -> Self {
+        KmcError::Io(err)
+    }
+}
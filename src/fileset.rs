@@ -0,0 +1,98 @@
+//! Query several data bases at once, see [KmcFileSet].
+use crate::{KmcError, KmcFile, KmcFileRa, Kmer};
+use std::path::Path;
+
+/// Several data bases over the same `k` opened in random access mode,
+/// e.g. one per sample.
+/// ```rust
+/// let samples = kmc_rs::KmcFileSet::open(&["data/test1", "data/test1"])?;
+/// let kmer = kmc_rs::Kmer::from("TAAGA")?;
+/// assert_eq!(samples.count_kmer(&kmer), vec![4, 4]);
+/// # Ok::<(), kmc_rs::KmcError>(())
+/// ```
+pub struct KmcFileSet {
+    files: Vec<KmcFileRa>,
+}
+
+impl KmcFileSet {
+    /// Open all `paths` (see [KmcFile::open_ra]) in the given order.
+    pub fn open<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<Self, KmcError> {
+        let files = paths
+            .into_iter()
+            .map(KmcFile::open_ra)
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_files(files)
+    }
+
+    /// Combine already opened `files`; fails unless they share the same `k`.
+    pub fn from_files(files: Vec<KmcFileRa>) -> Result<Self, KmcError> {
+        if let Some(first) = files.first() {
+            let k = first.kmer_length();
+            if let Some(other) = files.iter().find(|f| f.kmer_length() != k) {
+                return Err(KmcError::LengthMismatch {
+                    expected: k,
+                    got: other.kmer_length(),
+                });
+            }
+        }
+        Ok(Self { files })
+    }
+
+    /// The data bases in the order they were opened.
+    pub fn files(&self) -> &[KmcFileRa] {
+        &self.files
+    }
+
+    /// Number of data bases.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether there is no data base at all.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The counts of `kmer` in every data base (see [KmcFile::count_kmer]).
+    pub fn count_kmer(&self, kmer: &Kmer) -> Vec<usize> {
+        self.files.iter().map(|f| f.count_kmer(kmer)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use crate::KmcWriter;
+
+    #[test]
+    fn test_file_set() -> Result<(), KmcError> {
+        let set = KmcFileSet::open(vec!["./data/test1", "./data/test1"])?;
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.count_kmer(&Kmer::from("TAAGA")?), vec![4, 4]);
+        assert_eq!(set.count_kmer(&Kmer::from("TCTTA")?), vec![0, 0]);
+        assert!(KmcFileSet::open(Vec::<&str>::new())?.is_empty());
+        assert!(matches!(
+            KmcFileSet::open(["./data/test1", "./data/missing"]),
+            Err(KmcError::MissingFile { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_set_mismatch() -> Result<(), KmcError> {
+        let dir = TempDir::new("fileset")?;
+        let mut writer = KmcWriter::create(dir.join("k4"), 4)?;
+        writer.add(&Kmer::from("AAAA")?, 1)?;
+        writer.finish()?;
+        let result = KmcFileSet::open(vec![Path::new("./data/test1"), &dir.join("k4")]);
+        assert!(matches!(
+            result,
+            Err(KmcError::LengthMismatch {
+                expected: 5,
+                got: 4
+            })
+        ));
+        Ok(())
+    }
+}
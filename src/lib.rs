@@ -5,18 +5,129 @@
 //! let kmer = kmc_rs::Kmer::from("TAAGA")?;
 //! assert_eq!(db.kmer_length(), 5);     // we have an index over 5-mers
 //! assert_eq!(db.count_kmer(&kmer), 4); // "TAAGA" (or reverse complement) occurs 4 times
-//! # Ok::<(), String>(())
+//! # Ok::<(), kmc_rs::KmcError>(())
 //! ```
+pub mod complex;
 mod cxxbridge;
+pub mod encoding;
+mod error;
+mod fileset;
+#[cfg(feature = "mmap")]
+mod mmap;
+pub mod mode;
+mod options;
+mod pool;
+pub mod reads;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(test)]
+mod test_support;
+mod writer;
+
+pub use error::{KmcError, OpenMode};
+pub use fileset::KmcFileSet;
+#[cfg(feature = "mmap")]
+pub use mmap::KmcMmap;
+pub use options::KmcOpenOptions;
+pub use pool::KmcPool;
+pub use writer::KmcWriter;
+
+use encoding::{base_to_code, code_to_base};
+use std::path::Path;
+
+/// The largest `k` KMC counts kmers for.
+///
+/// A [Kmer] of any length up to (and beyond) this works; use [Kmer::with_len]
+/// and [Kmer::as_bytes] rather than the integer accessors for `k > 64`.
+pub const MAX_K: u32 = 256;
 
 /// A KMC data base; usually consisting of two files ending `.kmc_pre` and `.kmc_suf`.
 /// You can open a [KmcFile] in two modes:
 ///  * **random access mode** (see [KmcFile::open_ra]), and
 ///  * **iterator mode** (see [KmcFile::open_iter]).
-pub struct KmcFile {
+///
+/// The mode is part of the type (see [mode]), so only the methods valid
+/// in the respective mode are available.
+///
+/// A [KmcFile] can be moved to another thread.
+/// Moreover, a [KmcFileRa] is `Sync`, so it might be queried from several threads
+/// at once (e.g. behind an [Arc](std::sync::Arc)).
+pub struct KmcFile<M> {
     ptr: cxx::UniquePtr<cxxbridge::ffi::KmcFile>,
+    mode: std::marker::PhantomData<M>,
+    /// Path of the data base without extension.
+    prefix: String,
+    /// The kmer length, read once when opening: a [KmcFile] is only closed
+    /// when dropped, so it never changes.
+    k: u32,
+    /// Number of entries read by the listing since opening or restarting.
+    listed: usize,
+    /// Whether the last restart of the listing failed.
+    stalled: bool,
+}
+
+/// What the values stored in a data base mean, see [KmcFile::mode].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KmcMode {
+    /// The number of occurrences of every kmer (the default of `kmc`).
+    Occurrence,
+    /// Quality-aware counters (`kmc -q`).
+    Quality,
+    /// A mode unknown to this crate.
+    Other(u32),
+}
+
+/// The header of a data base, see [KmcFile::info].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KmcInfo {
+    /// See [KmcFile::kmer_length].
+    pub k: u32,
+    /// See [KmcFile::mode].
+    pub mode: KmcMode,
+    /// See [KmcFile::counter_size].
+    pub counter_size: u32,
+    /// See [KmcFile::lut_prefix_length].
+    pub lut_prefix_length: u32,
+    /// See [KmcFile::signature_len].
+    pub signature_len: u32,
+    /// See [KmcFile::min_count].
+    pub min_count: u32,
+    /// See [KmcFile::max_count].
+    pub max_count: u64,
+    /// See [KmcFile::both_strands].
+    pub both_strands: bool,
+    /// See [KmcFile::total_kmers].
+    pub total_kmers: u64,
 }
 
+/// Letter case of the symbols, see [Kmer::format_with].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    /// `ACGT`, as used by [Display](std::fmt::Display).
+    Upper,
+    /// `acgt`.
+    Lower,
+}
+
+/// Summary of all counts of a data base, see [KmcFile::count_stats].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CountStats {
+    /// Sum of all counts.
+    pub total: u64,
+    /// Number of distinct kmers.
+    pub distinct: u64,
+    /// Largest count (`0` if there are no kmers).
+    pub max: usize,
+    /// Average count (`0.0` if there are no kmers).
+    pub mean: f64,
+}
+
+/// A [KmcFile] opened in random access mode.
+pub type KmcFileRa = KmcFile<mode::RandomAccess>;
+
+/// A [KmcFile] opened in iterator mode.
+pub type KmcFileIter = KmcFile<mode::Listing>;
+
 /// Binary representation of a kmer to be queried by [KmcFile::count_kmer].
 pub struct Kmer {
     handle: cxx::UniquePtr<cxxbridge::ffi::Kmer>,
@@ -24,297 +135,3565 @@ pub struct Kmer {
 
 #[doc(hidden)]
 pub struct KmcFileIterU64<'a> {
-    file: &'a mut KmcFile,
+    file: &'a mut KmcFileIter,
     kmer: Kmer,
 }
 
-impl KmcFile {
-    /// Open in random access mode.
-    /// The file name `fname` must not include the suffixes `.kmc_pre` or `.kmc_suf`.
-    /// The file is automatically closed by [Drop].
-    pub fn open_ra(fname: &str) -> Result<Self, String> {
-        let mut ptr = cxxbridge::ffi::new_ckmc_file();
-        if ptr.pin_mut().open_for_ra(fname) {
-            Ok(Self { ptr })
-        } else {
-            Err(format!("Could not open '{}' for random access", fname))
+/// Step through a listing while keeping the current entry, see [KmcFile::cursor].
+pub struct KmcCursor<'a> {
+    file: &'a mut KmcFileIter,
+    kmer: Kmer,
+    count: usize,
+}
+
+impl<M: mode::Mode> KmcFile<M> {
+    fn open(path: &Path, validate_exists: bool) -> Result<Self, KmcError> {
+        let fname = db_prefix(path)?;
+        if validate_exists {
+            for suffix in &[".kmc_pre", ".kmc_suf"] {
+                let path = std::path::PathBuf::from(format!("{}{}", fname, suffix));
+                if let Err(source) = std::fs::File::open(&path) {
+                    return Err(KmcError::MissingFile { path, source });
+                }
+            }
         }
+        Self::open_with(cxxbridge::ffi::new_ckmc_file(), fname, !validate_exists)
     }
 
-    /// Open in iterator mode (also called „listing“ mode).
-    /// The file name `fname` must not include the suffixes `.kmc_pre` or `.kmc_suf`.
-    /// The file is automatically closed by [Drop].
-    pub fn open_iter(fname: &str) -> Result<Self, String> {
-        let mut ptr = cxxbridge::ffi::new_ckmc_file();
-        if ptr.pin_mut().open_for_iter(fname) {
-            Ok(Self { ptr })
+    /// Open the data base `fname` (without suffix) using the closed `ptr`;
+    /// with `probe`, the C++ side checks the files first to report why they
+    /// cannot be opened (unnecessary if [KmcFile::open] already checked).
+    fn open_with(
+        mut ptr: cxx::UniquePtr<cxxbridge::ffi::KmcFile>,
+        fname: &str,
+        probe: bool,
+    ) -> Result<Self, KmcError> {
+        let opened = match M::MODE {
+            OpenMode::RandomAccess => ptr.pin_mut().open_for_ra(fname, probe),
+            OpenMode::Listing => ptr.pin_mut().open_for_iter(fname, probe),
+        };
+        if opened {
+            Ok(Self::new(ptr, fname))
         } else {
-            Err(format!("Could not open '{}' in listing mode", fname))
+            Err(KmcError::OpenFailed {
+                path: fname.to_string(),
+                mode: M::MODE,
+                reason: ptr.open_error(),
+            })
         }
     }
+}
 
-    /// The parameter `k` when this data base was constructed with.
-    pub fn kmer_length(&self) -> u32 {
-        self.ptr.kmer_len()
+impl<M> KmcFile<M> {
+    /// Close the data base and open it again in mode `N`, reusing the
+    /// underlying KMC object and the path.
+    fn reopen<N: mode::Mode>(mut self) -> Result<KmcFile<N>, KmcError> {
+        let mut ptr = std::mem::replace(&mut self.ptr, cxx::UniquePtr::null());
+        let prefix = std::mem::take(&mut self.prefix);
+        if !ptr.pin_mut().close() {
+            return Err(KmcError::OpenFailed {
+                path: prefix,
+                mode: N::MODE,
+                reason: "could not close the data base first".to_string(),
+            });
+        }
+        KmcFile::open_with(ptr, &prefix, true)
     }
+}
 
-    /// Start a new iterator yielding 64-bit encoded kmer items
-    /// `(kmer, count): (u64, usize)`.
-    ///
-    /// For example, count all kmers starting with `"TG"`
-    /// ```
-    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
-    /// assert_eq!(db.kmer_length(), 5);
-    /// let mut kmer = kmc_rs::Kmer::with_k(5);
-    /// let mut count_tg = 0;
-    /// while let Some(count) = db.read_next(&mut kmer) {
-    ///     if kmer.as_u64() >> 3 == 0b11_10 {
-    ///         count_tg += count;
-    ///     }
-    /// }
-    /// assert_eq!(count_tg, 18);
-    /// # Ok::<(), String>(())
-    /// ```
-    ///
-    /// Only works when opened as [KmcFile::open_iter].
-    pub fn iter_u64<'a>(&'a mut self) -> KmcFileIterU64<'a> {
-        use std::convert::TryInto;
+impl<M> KmcFile<M> {
+    fn new(ptr: cxx::UniquePtr<cxxbridge::ffi::KmcFile>, prefix: &str) -> Self {
+        Self {
+            k: ptr.kmer_len(),
+            ptr,
+            mode: std::marker::PhantomData,
+            prefix: prefix.to_string(),
+            listed: 0,
+            stalled: false,
+        }
+    }
 
-        let k = self.kmer_length().try_into().unwrap();
-        KmcFileIterU64 {
-            file: self,
-            kmer: Kmer::with_k(k),
+    /// All parameters recorded in the header of the data base at once.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// let info = db.info();
+    /// assert_eq!((info.k, info.total_kmers), (5, 291));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn info(&self) -> KmcInfo {
+        KmcInfo {
+            k: self.kmer_length(),
+            mode: self.mode(),
+            counter_size: self.counter_size(),
+            lut_prefix_length: self.lut_prefix_length(),
+            signature_len: self.signature_len(),
+            min_count: self.min_count(),
+            max_count: self.max_count(),
+            both_strands: self.both_strands(),
+            total_kmers: self.total_kmers(),
         }
     }
 
-    /// Number of (canical) k-mers in the data base.
+    /// The parameter `k` when this data base was constructed with.
     ///
-    /// It might be necessary to iterate through the whole file; that is why a `&mut self`
-    /// is needed, here.
-    pub fn num_kmers(&mut self) -> usize {
-        self.ptr.pin_mut().kmer_count()
+    /// Cached when opening, so this does not cross the FFI boundary.
+    pub fn kmer_length(&self) -> u32 {
+        self.k
     }
 
-    /// How often is the canonical `kmer` recorded in the data base?
-    /// Only works when opened as [KmcFile::open_ra].
-    pub fn count_kmer(&self, kmer: &Kmer) -> usize {
-        self.ptr.check_kmer(&kmer.handle)
+    /// Kmers occurring less often were not recorded in the data base
+    /// (parameter `-ci` when running `kmc`).
+    pub fn min_count(&self) -> u32 {
+        self.ptr.min_count()
     }
 
-    /// Reset the file pointer to the beginning.
-    /// Only useful when opened as [KmcFile::open_iter].
-    pub fn restart(&mut self) -> bool {
-        self.ptr.pin_mut().restart_listing()
+    /// Kmers occurring more often were not recorded in the data base
+    /// (parameter `-cx` when running `kmc`).
+    pub fn max_count(&self) -> u64 {
+        self.ptr.max_count()
     }
 
-    /// Read next entry into `kmer`.
+    /// Whether both strands were counted, i.e. only [canonical] kmers are stored
+    /// (this is the default of `kmc`, unless run with `-b`).
     ///
-    /// If there was one available return `Some(count)`; otherwise
-    /// return None to indicate the end of the file
-    /// ([KmcFile::restart] might be useful then).
+    /// In that case, [KmcFile::count_kmer] needs the canonical form to find a kmer.
     ///
-    /// Only works when opened as [KmcFile::open_iter].
-    pub fn read_next(&mut self, kmer: &mut Kmer) -> Option<usize> {
-        if kmer.len() == self.kmer_length() {
-            unsafe { self.read_next_unchecked(kmer) }
-        } else {
-            None
-        }
+    /// [canonical]: Kmer::canonical
+    pub fn both_strands(&self) -> bool {
+        self.ptr.both_strands()
     }
 
-    /// Like [KmcFile::read_next] but do not check the lengths.
-    ///
-    /// # Safety
-    /// Might crash when `self.kmer_length() != kmer.len()`.
-    pub unsafe fn read_next_unchecked(&mut self, kmer: &mut Kmer) -> Option<usize> {
-        let mut count = 0;
-        if self.ptr.pin_mut().next(kmer.handle.pin_mut(), &mut count) {
-            Some(count)
-        } else {
-            None
+    /// Construct a kmer from `seq` fitting to this data base, i.e. fail unless its
+    /// length is [KmcFile::kmer_length].
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.make_kmer("TAAGA")?.to_string(), "TAAGA");
+    /// assert!(db.make_kmer("TAAG").is_err());
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn make_kmer(&self, seq: &str) -> Result<Kmer, KmcError> {
+        let kmer = Kmer::from(seq)?;
+        self.check_length(&kmer)?;
+        Ok(kmer)
+    }
+
+    /// Whether `kmer` has the length [KmcFile::kmer_length], i.e. might be
+    /// queried or read into.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert!(db.accepts(&kmc_rs::Kmer::from("TAAGA")?));
+    /// assert!(!db.accepts(&kmc_rs::Kmer::from("TAAG")?));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    #[inline]
+    pub fn accepts(&self, kmer: &Kmer) -> bool {
+        kmer.len() == self.kmer_length()
+    }
+
+    fn check_length(&self, kmer: &Kmer) -> Result<(), KmcError> {
+        if !self.accepts(kmer) {
+            return Err(KmcError::LengthMismatch {
+                expected: self.kmer_length(),
+                got: kmer.len(),
+            });
         }
+        Ok(())
     }
-}
 
-impl Drop for KmcFile {
-    fn drop(&mut self) {
-        if !self.ptr.pin_mut().close() {
-            panic!("error while closing");
+    /// What the stored counts mean.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.mode(), kmc_rs::KmcMode::Occurrence);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn mode(&self) -> KmcMode {
+        match self.ptr.kmc_mode() {
+            0 => KmcMode::Occurrence,
+            1 => KmcMode::Quality,
+            other => KmcMode::Other(other),
         }
     }
-}
 
-impl<'a> Iterator for KmcFileIterU64<'a> {
-    type Item = (u64, usize);
+    /// Number of bytes (1 to 4) used to store each count.
+    ///
+    /// Counts are clamped to `2^(8 * counter_size) - 1` (and to [KmcFile::max_count]).
+    pub fn counter_size(&self) -> u32 {
+        self.ptr.counter_size()
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        unsafe {
-            self.file
-                .read_next_unchecked(&mut self.kmer)
-                .map(|c| (self.kmer.as_u64(), c))
-        }
+    /// Number of leading symbols of every kmer that are encoded by the look up
+    /// table in the `.kmc_pre` file rather than stored in the `.kmc_suf` file.
+    pub fn lut_prefix_length(&self) -> u32 {
+        self.ptr.lut_prefix_length()
     }
-}
 
-impl Kmer {
-    /// Construct a kmer by a `&str`.
-    pub fn from(kmer: &str) -> Result<Self, String> {
-        let mut handle = cxxbridge::ffi::new_kmerapi();
-        if !handle.pin_mut().from_string(kmer) {
-            return Err(format!("Internal Error in CKmerApi::from_string"));
-        }
-        Ok(Self { handle })
+    /// Length of the signatures (minimizers) used to distribute the kmers into
+    /// bins; `0` for data bases in the format of KMC 1, which have no signatures.
+    pub fn signature_len(&self) -> u32 {
+        self.ptr.signature_len()
     }
 
-    /// Construct a new kmer and reserve space for `k` symbols.
-    pub fn with_k(k: u8) -> Self {
-        Self {
-            handle: cxxbridge::ffi::new_kmerapi_with_len(k as u32),
+    /// Fail with [KmcError::Unsorted] for data bases of KMC 2, whose
+    /// listing is not sorted.
+    pub(crate) fn check_sorted(&self) -> Result<(), KmcError> {
+        if self.signature_len() == 0 {
+            Ok(())
+        } else {
+            Err(KmcError::Unsorted {
+                path: self.prefix.clone(),
+            })
         }
     }
 
-    /// Number of symbols `k` of this kmer.
-    pub fn len(&self) -> u32 {
-        self.handle.kmer_len()
+    /// Estimated number of bytes KMC allocates when opening this data base for
+    /// random access, computed from the header like KMC does:
+    /// the look up table of `4^lut_prefix_length + 1` offsets and all suffix
+    /// records of `(k - lut_prefix_length) / 4 + counter_size` bytes each.
+    ///
+    /// For data bases in the format of KMC 2, the signature map is added, but
+    /// the look up table exists once per bin, which is not accounted for.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.estimated_ram_bytes(), 5 * 8 + 291 * (1 + 1));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn estimated_ram_bytes(&self) -> u64 {
+        let lut = self.lut_prefix_length();
+        let lut_bytes = ((1u64 << (2 * lut)) + 1) * 8;
+        let record_bytes = ((self.kmer_length() - lut) / 4 + self.counter_size()) as u64;
+        let signature_bytes = match self.signature_len() {
+            0 => 0,
+            len => ((1u64 << (2 * len)) + 1) * 4,
+        };
+        lut_bytes + self.total_kmers() * record_bytes + signature_bytes
     }
 
-    /// Construct a kmer from bit encoded kmer `val` with `k` symbols.
-    /// Note: `k` must be at most `32`!
-    /// See [Kmer::set_u64] for further details.
-    pub fn from_u64(k: u8, val: u64) -> Self {
-        let mut kmer = Self::with_k(k);
-        kmer.set_u64(val);
-        kmer
+    /// Number of (canonical) k-mers in the data base.
+    ///
+    /// As the count cutoffs of the data base are never changed, this is
+    /// [KmcFile::total_kmers], so it does not disturb the position of a listing.
+    pub fn num_kmers(&self) -> usize {
+        self.total_kmers() as usize
     }
 
-    /// Reset the kmer to a new bit encoded kmer of same length.
-    /// Note: length `k` must be at most `32`!
+    /// Number of (canonical) k-mers in the data base as recorded in its header.
     ///
-    /// The coding is as usual:
-    ///  - `A` -> `0b00`
-    ///  - `C` -> `0b01`
-    ///  - `T` -> `0b10`
-    ///  - `G` -> `0b11`
+    /// Note that KMC does not record the sum of all counts.
+    pub fn total_kmers(&self) -> u64 {
+        self.ptr.total_kmers()
+    }
+}
+
+impl KmcFileRa {
+    /// Open in random access mode.
+    /// A suffix `.kmc_pre` or `.kmc_suf` of `path` is ignored.
+    /// The file is automatically closed by [Drop].
+    pub fn open_ra(path: impl AsRef<Path>) -> Result<Self, KmcError> {
+        Self::open(path.as_ref(), true)
+    }
+
+    /// Whether `path` is a data base that can be opened (see [KmcFile::open_ra]).
     ///
-    /// # Example
+    /// The data base is closed again right away.
     /// ```rust
-    /// let mut kmer = kmc_rs::Kmer::with_k(5);
-    /// kmer.set_u64(0b11_00_00_10_00);
-    /// assert_eq!(kmer.to_string(), "TAAGA");
+    /// assert!(kmc_rs::KmcFile::is_valid("data/test1"));
+    /// assert!(!kmc_rs::KmcFile::is_valid("data/missing"));
     /// ```
-    #[inline]
-    pub fn set_u64(&mut self, val: u64) {
-        debug_assert!(self.len() <= 32);
-        self.handle.pin_mut().set_u64(val);
+    pub fn is_valid(path: impl AsRef<Path>) -> bool {
+        Self::open_ra(path).is_ok()
     }
 
-    /// Obtain the first 64 bits of this Kmer.
-    /// When `self.len() > 32` the bits are incomplete.
+    /// Switch to iterator mode without dropping `self` first.
+    ///
+    /// KMC has to read the data base again (its API offers no way to share
+    /// the already loaded parts), but the path need not be passed again.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.count_str("TAAGA")?, 4);
+    /// let mut db = db.reopen_as_iter()?;
+    /// assert_eq!(db.iter_kmers().count(), 291);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn reopen_as_iter(self) -> Result<KmcFileIter, KmcError> {
+        self.reopen()
+    }
+
+    /// How often is the canonical `kmer` recorded in the data base?
+    /// The `kmer` is looked up as is, so if [KmcFile::both_strands] holds,
+    /// pass the [canonical](Kmer::canonical) form.
+    ///
+    /// This is the fast path: a kmer whose length differs from
+    /// [KmcFile::kmer_length] (e.g. an [empty](Kmer::is_empty) one) silently
+    /// counts `0`.
+    /// Use [KmcFile::try_count_kmer] to get an error instead.
+    pub fn count_kmer(&self, kmer: &Kmer) -> usize {
+        self.ptr.check_kmer(&kmer.handle)
+    }
+
+    /// Like [KmcFile::count_kmer] but report `0` if the count is below `min`,
+    /// on top of the thresholds the data base was built with.
     /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
     /// let kmer = kmc_rs::Kmer::from("TAAGA")?;
-    /// assert_eq!(kmer.as_u64(), 0b11_00_00_10_00);
-    /// Ok::<(), String>(())
+    /// assert_eq!(db.count_kmer_thresholded(&kmer, 4), 4);
+    /// assert_eq!(db.count_kmer_thresholded(&kmer, 5), 0);
+    /// # Ok::<(), kmc_rs::KmcError>(())
     /// ```
-    #[inline]
-    pub fn as_u64(&self) -> u64 {
-        self.handle.as_u64()
+    pub fn count_kmer_thresholded(&self, kmer: &Kmer, min: usize) -> usize {
+        let count = self.count_kmer(kmer);
+        if count >= min {
+            count
+        } else {
+            0
+        }
     }
 
-    #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
+    /// The counts `(forward, reverse)` of `kmer` and of its
+    /// [reverse complement](Kmer::reverse_complement), looked up separately.
+    ///
+    /// This distinguishes the strands if the data base counts a single strand.
+    /// If it counts [both strands](KmcFile::both_strands), only the canonical
+    /// one of the two is stored, so the other count is `0` (unless `kmer` is
+    /// its own reverse complement).
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.count_both_strands(&kmc_rs::Kmer::from("TAAGA")?), (4, 0));
+    /// assert_eq!(db.count_both_strands(&kmc_rs::Kmer::from("TCTTA")?), (0, 4));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn count_both_strands(&self, kmer: &Kmer) -> (usize, usize) {
+        (
+            self.count_kmer(kmer),
+            self.count_kmer(&kmer.reverse_complement()),
+        )
     }
-}
 
-impl std::fmt::Display for cxxbridge::ffi::Kmer {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        f.write_str(&self.to_string())
+    /// Like [KmcFile::count_kmer] but fail if the length of `kmer` is not
+    /// [KmcFile::kmer_length].
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.try_count_kmer(&kmc_rs::Kmer::from("TAAGA")?)?, 4);
+    /// assert!(db.try_count_kmer(&kmc_rs::Kmer::from("TAAGAC")?).is_err());
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn try_count_kmer(&self, kmer: &Kmer) -> Result<usize, KmcError> {
+        self.check_length(kmer)?;
+        Ok(self.count_kmer(kmer))
     }
-}
 
-impl std::fmt::Display for Kmer {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        self.handle.fmt(f)
+    /// Whether `kmer` is recorded in the data base (see [KmcFile::count_kmer]);
+    /// fails if its length is not [KmcFile::kmer_length].
+    ///
+    /// KMC has no cheaper presence query, so this looks up the count.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert!(db.contains(&kmc_rs::Kmer::from("TAAGA")?)?);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn contains(&self, kmer: &Kmer) -> Result<bool, KmcError> {
+        Ok(self.try_count_kmer(kmer)? > 0)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Count many `kmers` at once; the `i`-th count belongs to `kmers[i]`.
+    ///
+    /// Same result as calling [KmcFile::count_kmer] for every kmer,
+    /// but the loop runs in C++.
+    pub fn count_kmers(&self, kmers: &[Kmer]) -> Vec<usize> {
+        let k = self.kmer_length();
+        let rows = Kmer::with_len(k).handle.data().len();
+        let mut data = Vec::with_capacity(kmers.len() * rows);
+        for kmer in kmers {
+            if kmer.len() == k {
+                data.extend_from_slice(kmer.handle.data());
+            } else {
+                data.resize(data.len() + rows, 0);
+            }
+        }
+        let mut counts = Vec::with_capacity(kmers.len());
+        self.ptr.check_kmers(&data, &mut counts);
+        for (count, kmer) in counts.iter_mut().zip(kmers) {
+            if kmer.len() != k {
+                *count = 0;
+            }
+        }
+        counts
+    }
 
-    #[test]
-    fn test_open() -> Result<(), String> {
-        let mut io = KmcFile::open_ra("./data/test1")?;
-        assert_eq!(io.kmer_length(), 5);
-        assert_eq!(io.num_kmers(), 291);
-        Ok(())
+    /// Like [KmcFile::count_kmers] but distributed over the threads of [rayon]'s pool.
+    ///
+    /// Requires the crate feature `rayon`.
+    /// Concurrent queries are fine, see the `Sync` implementation of [KmcFileRa].
+    #[cfg(feature = "rayon")]
+    pub fn par_count_kmers(&self, kmers: &[Kmer]) -> Vec<usize> {
+        use rayon::prelude::*;
+
+        kmers.par_iter().map(|kmer| self.count_kmer(kmer)).collect()
     }
 
-    #[test]
-    fn test_kmer() -> Result<(), String> {
-        let kmer = Kmer::from("TAAGA")?;
-        let s = kmer.to_string();
-        assert_eq!(&s, "TAAGA", "got {}", &s);
-        Ok(())
+    /// Like [KmcFile::count_kmer] but for a kmer given as string, e.g.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.count_str("TAAGA")?, 4);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    ///
+    /// Fails if `seq` is not a valid kmer of length [KmcFile::kmer_length].
+    pub fn count_str(&self, seq: &str) -> Result<usize, KmcError> {
+        Ok(self.count_kmer(&self.make_kmer(seq)?))
     }
 
-    #[test]
-    fn test_kmer_errors() {
-        assert!(Kmer::from("TCN").is_err());
-        assert!(Kmer::from("actG").is_ok());
+    /// Like [KmcFile::count_str] but write the kmer into `scratch` instead of
+    /// allocating a new [Kmer] for every query.
+    ///
+    /// If the length of `scratch` is not [KmcFile::kmer_length], it is replaced
+    /// by a kmer of the right length first (so only the first call allocates).
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// let mut scratch = kmc_rs::Kmer::with_k(5);
+    /// for seq in &["TAAGA", "AAAAA", "AAAAG"] {
+    ///     assert_eq!(db.count_into(&mut scratch, seq)?, db.count_str(seq)?);
+    /// }
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn count_into(&self, scratch: &mut Kmer, seq: &str) -> Result<usize, KmcError> {
+        let seq = seq.trim_matches(|c: char| c.is_ascii_whitespace());
+        if seq.len() != self.kmer_length() as usize {
+            return Err(KmcError::LengthMismatch {
+                expected: self.kmer_length(),
+                got: seq.len() as u32,
+            });
+        }
+        if seq.bytes().any(|b| base_to_code(b).is_none()) {
+            return Err(KmcError::InvalidKmer(seq.to_string()));
+        }
+        if !self.accepts(scratch) {
+            *scratch = Kmer::with_len(self.kmer_length());
+        }
+        scratch.set_codes(seq.bytes().filter_map(base_to_code));
+        Ok(self.count_kmer(scratch))
     }
 
-    #[test]
-    fn test_count_kmer() -> Result<(), String> {
-        let mut kmer = Kmer::from("TAAGA")?;
-        let io = KmcFile::open_ra("./data/test1")?;
-        assert_eq!(io.count_kmer(&mut kmer), 4);
-        Ok(())
+    /// Count all kmers of the read `seq` at once; the `i`-th count belongs to
+    /// the kmer starting at position `i`, i.e. there are `seq.len() - k + 1` counts
+    /// (none if `seq` is shorter than `k`).
+    ///
+    /// Kmers containing a symbol other than `ACGT` (e.g. `N`) have count `0`.
+    /// Unlike [KmcFile::count_kmer], KMC canonicalizes the kmers if the data base
+    /// counts both strands.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.counts_for_read("TCTTAAGACG")?, vec![4, 2, 2, 4, 2, 0]);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    ///
+    /// Much faster than calling [KmcFile::count_kmer] for every window.
+    pub fn counts_for_read(&self, seq: &str) -> Result<Vec<usize>, KmcError> {
+        self.counts_for_read_bytes(seq.as_bytes())
     }
 
-    #[test]
-    fn test_from_u64_tcaaccttggaa() {
-        assert_eq!("TCAACCTTGGAA".len(), 12);
-        let kmer = Kmer::from_u64(12, 0b1101_0000_0101_1111_1010_0000);
-        assert_eq!(kmer.to_string(), "TCAACCTTGGAA");
+    /// Like [KmcFile::counts_for_read] but for a read that is not (known to be)
+    /// valid UTF-8, e.g. a line of a FASTQ file.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.counts_for_read_bytes(b"TCTTAAGACG")?, vec![4, 2, 2, 4, 2, 0]);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn counts_for_read_bytes(&self, seq: &[u8]) -> Result<Vec<usize>, KmcError> {
+        let mut counts = Vec::new();
+        if self.ptr.counts_for_read(seq, &mut counts) {
+            Ok(counts)
+        } else {
+            Err(KmcError::Kmc(format!(
+                "count the kmers of the read of length {}",
+                seq.len()
+            )))
+        }
     }
 
-    #[test]
-    fn test_from_u64_ttttttttttttttttttttttttttttttc() {
-        assert_eq!("TTTTTTTTTTTTTTTTTTTTTTTTTTTTTTC".len(), 31);
-        assert_eq!(
-            "TTTTTTTTTTTTTTTTTTTTTTTTTTTTTTC",
-            Kmer::from_u64(
-                31,
-                0b1111111111111111111111111111111111111111111111111111111111111_01,
-            )
-            .to_string()
-        );
+    /// Sum of the [counts](KmcFile::counts_for_read) of all windows of the read `seq`.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.read_coverage("TCTTAAGACG")?, 4 + 2 + 2 + 4 + 2);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn read_coverage(&self, seq: &str) -> Result<usize, KmcError> {
+        Ok(self.counts_for_read(seq)?.into_iter().sum())
     }
 
-    #[test]
-    fn test_open_iter() -> Result<(), String> {
-        let io = KmcFile::open_iter("./data/test1")?;
-        assert_eq!(io.kmer_length(), 5);
-        Ok(())
+    /// Fraction of the windows of the read `seq` that occur in the data base,
+    /// i.e. have a positive [count](KmcFile::counts_for_read).
+    ///
+    /// A read shorter than [KmcFile::kmer_length] has no windows; its fraction
+    /// is `0.0` (no evidence), not an error.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.read_hit_fraction("TCTTAAGACG")?, 5.0 / 6.0);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn read_hit_fraction(&self, seq: &str) -> Result<f64, KmcError> {
+        let counts = self.counts_for_read(seq)?;
+        if counts.is_empty() {
+            return Ok(0.0);
+        }
+        let hits = counts.iter().filter(|&&count| count > 0).count();
+        Ok(hits as f64 / counts.len() as f64)
     }
 
-    #[test]
-    fn test_iter_count() -> Result<(), String> {
-        assert_eq!(KmcFile::open_iter("./data/test1")?.iter_u64().count(), 291);
+    /// Like [KmcFile::counts_for_read] but only for every `stride`-th window,
+    /// i.e. the windows starting at `0, stride, 2 * stride, ...`.
+    ///
+    /// Only these windows are looked up (by [KmcFile::count_kmers]).
+    ///
+    /// # Panics
+    /// If `stride` is zero, like [Iterator::step_by].
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// assert_eq!(db.counts_for_read_strided("TCTTAAGACG", 2)?, vec![4, 2, 2]);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn counts_for_read_strided(
+        &self,
+        seq: &str,
+        stride: usize,
+    ) -> Result<Vec<usize>, KmcError> {
+        assert!(stride != 0, "stride must be positive");
+        let k = self.kmer_length();
+        let bytes = seq.as_bytes();
+        let starts = (0..(bytes.len() + 1).saturating_sub(k as usize)).step_by(stride);
+        let mut counts = vec![0; starts.len()];
+        // windows with a symbol other than `ACGT` keep count 0
+        let mut kmers = Vec::with_capacity(counts.len());
+        let mut slots = Vec::with_capacity(counts.len());
+        for (slot, start) in starts.enumerate() {
+            let window = &bytes[start..start + k as usize];
+            if let Some(codes) = window
+                .iter()
+                .map(|&b| base_to_code(b))
+                .collect::<Option<Vec<u8>>>()
+            {
+                let kmer = Kmer::from_codes(k, codes);
+                kmers.push(if self.both_strands() {
+                    kmer.canonical()
+                } else {
+                    kmer
+                });
+                slots.push(slot);
+            }
+        }
+        for (slot, count) in slots.into_iter().zip(self.count_kmers(&kmers)) {
+            counts[slot] = count;
+        }
+        Ok(counts)
+    }
+
+    /// Like [KmcFile::counts_for_read] but windows containing a symbol other
+    /// than `ACGT` (e.g. `N`) are `None` rather than `Some(0)`.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// let counts = db.counts_for_read_masked("TCTTAAGACN")?;
+    /// assert_eq!(counts, vec![Some(4), Some(2), Some(2), Some(4), Some(2), None]);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn counts_for_read_masked(&self, seq: &str) -> Result<Vec<Option<usize>>, KmcError> {
+        let k = self.kmer_length() as usize;
+        let bytes = seq.as_bytes();
+        // the window starting at `i` is valid iff the last invalid symbol is before `i`
+        let mut next_valid = 0;
+        let mut valid = Vec::with_capacity(bytes.len());
+        for (i, &b) in bytes.iter().enumerate() {
+            if base_to_code(b).is_none() {
+                next_valid = i + 1;
+            }
+            if i + 1 >= k {
+                valid.push(next_valid + k <= i + 1);
+            }
+        }
+        Ok(self
+            .counts_for_read(seq)?
+            .into_iter()
+            .zip(valid)
+            .map(|(count, valid)| if valid { Some(count) } else { None })
+            .collect())
+    }
+}
+
+impl KmcFileIter {
+    /// Open in iterator mode (also called „listing“ mode).
+    /// A suffix `.kmc_pre` or `.kmc_suf` of `path` is ignored.
+    /// The file is automatically closed by [Drop].
+    pub fn open_iter(path: impl AsRef<Path>) -> Result<Self, KmcError> {
+        Self::open(path.as_ref(), true)
+    }
+
+    /// Switch to random access mode, see [KmcFile::reopen_as_iter].
+    pub fn reopen_as_ra(self) -> Result<KmcFileRa, KmcError> {
+        self.reopen()
+    }
+
+    /// A kmer of length [KmcFile::kmer_length] to be filled by [KmcFile::read_next].
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let mut kmer = db.new_kmer_buffer();
+    /// assert_eq!(db.read_next(&mut kmer), Some(2));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn new_kmer_buffer(&self) -> Kmer {
+        Kmer::with_len(self.kmer_length())
+    }
+
+    /// Start a new iterator yielding 64-bit encoded kmer items
+    /// `(kmer, count): (u64, usize)`.
+    ///
+    /// For example, count all kmers starting with `"TG"`
+    /// ```
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// assert_eq!(db.kmer_length(), 5);
+    /// let mut kmer = kmc_rs::Kmer::with_k(5);
+    /// let mut count_tg = 0;
+    /// while let Some(count) = db.read_next(&mut kmer) {
+    ///     if kmer.as_u64() >> 3 == 0b11_10 {
+    ///         count_tg += count;
+    ///     }
+    /// }
+    /// assert_eq!(count_tg, 18);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn iter_u64<'a>(&'a mut self) -> KmcFileIterU64<'a> {
+        let kmer = self.new_kmer_buffer();
+        KmcFileIterU64 { file: self, kmer }
+    }
+
+    /// Like [KmcFile::iter_u64] but call `progress` with the number of items
+    /// yielded so far after every `every` items (never if `every == 0`),
+    /// e.g. to update a progress bar.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let mut reported = Vec::new();
+    /// let n = db.iter_u64_with_progress(100, |n| reported.push(n)).count();
+    /// assert_eq!((n, reported), (291, vec![100, 200]));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn iter_u64_with_progress<'a, F>(
+        &'a mut self,
+        every: usize,
+        mut progress: F,
+    ) -> impl Iterator<Item = (u64, usize)> + 'a
+    where
+        F: FnMut(usize) + 'a,
+    {
+        self.iter_u64().enumerate().map(move |(i, item)| {
+            if every != 0 && (i + 1) % every == 0 {
+                progress(i + 1);
+            }
+            item
+        })
+    }
+
+    /// Like [KmcFile::iter_u64] but every item `(kmer, count, canonical)` also
+    /// tells whether the kmer is [canonical](Kmer::is_canonical).
+    ///
+    /// If the data base counts [both strands](KmcFile::both_strands), KMC only
+    /// stores canonical kmers, so `canonical` always holds.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// assert!(db.iter_u64_canonical().all(|(_, _, canonical)| canonical));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn iter_u64_canonical(&mut self) -> impl Iterator<Item = (u64, usize, bool)> + '_ {
+        let both_strands = self.both_strands();
+        let mut kmer = Kmer::with_len(self.kmer_length());
+        std::iter::from_fn(move || {
+            unsafe { self.read_next_unchecked(&mut kmer) }
+                .map(|count| (kmer.as_u64(), count, both_strands || kmer.is_canonical()))
+        })
+    }
+
+    /// Start a cursor over the remaining entries.
+    ///
+    /// Unlike an iterator, the current entry can be inspected again and again:
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let mut cursor = db.cursor();
+    /// while cursor.advance() {
+    ///     if cursor.current_count() > 8 {
+    ///         break;
+    ///     }
+    /// }
+    /// assert_eq!(cursor.current_kmer().to_string(), "CGACA");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn cursor(&mut self) -> KmcCursor<'_> {
+        let k = self.kmer_length();
+        KmcCursor {
+            file: self,
+            kmer: Kmer::with_len(k),
+            count: 0,
+        }
+    }
+
+    /// Like [KmcFile::iter_u64] but taking ownership of the file, which is
+    /// closed when the iterator is dropped.
+    /// ```rust
+    /// fn entries(path: &str) -> Result<impl Iterator<Item = (u64, usize)>, kmc_rs::KmcError> {
+    ///     Ok(kmc_rs::KmcFile::open_iter(path)?.into_iter_u64())
+    /// }
+    ///
+    /// assert_eq!(entries("data/test1")?.count(), 291);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn into_iter_u64(mut self) -> impl Iterator<Item = (u64, usize)> {
+        let mut kmer = self.new_kmer_buffer();
+        std::iter::from_fn(move || {
+            unsafe { self.read_next_unchecked(&mut kmer) }.map(|count| (kmer.as_u64(), count))
+        })
+    }
+
+    /// Iterate over all remaining entries `(kmer, count)`.
+    ///
+    /// Unlike [KmcFile::iter_u64], every item owns a freshly allocated [Kmer],
+    /// so this works for any `k` and the items can be collected.
+    pub fn iter_kmers(&mut self) -> impl Iterator<Item = (Kmer, usize)> + '_ {
+        let k = self.kmer_length();
+        std::iter::from_fn(move || {
+            let mut kmer = Kmer::with_len(k);
+            unsafe { self.read_next_unchecked(&mut kmer) }.map(|count| (kmer, count))
+        })
+    }
+
+    /// List the remaining entries of `self` and `other` side by side (both
+    /// are sorted) and yield every kmer occurring in either of them with
+    /// its count in `self` and in `other`, in increasing order.
+    ///
+    /// Fails if the kmer lengths differ, and with [KmcError::Unsorted] if
+    /// either is a data base of KMC 2 (see [KmcFile::signature_len]).
+    /// ```rust
+    /// let mut a = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let mut b = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let (kmer, ca, cb) = a.merge_with(&mut b)?.next().unwrap();
+    /// assert_eq!((kmer.to_string(), ca, cb), ("AAAAA".to_string(), Some(2), Some(2)));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn merge_with<'a>(
+        &'a mut self,
+        other: &'a mut KmcFileIter,
+    ) -> Result<impl Iterator<Item = (Kmer, Option<usize>, Option<usize>)> + 'a, KmcError> {
+        if self.kmer_length() != other.kmer_length() {
+            return Err(KmcError::LengthMismatch {
+                expected: self.kmer_length(),
+                got: other.kmer_length(),
+            });
+        }
+        self.check_sorted()?;
+        other.check_sorted()?;
+        let mut a = self.iter_kmers().peekable();
+        let mut b = other.iter_kmers().peekable();
+        Ok(std::iter::from_fn(move || {
+            let order = match (a.peek(), b.peek()) {
+                (None, None) => return None,
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (Some((x, _)), Some((y, _))) => x.cmp(y),
+            };
+            match order {
+                std::cmp::Ordering::Less => a.next().map(|(kmer, ca)| (kmer, Some(ca), None)),
+                std::cmp::Ordering::Greater => b.next().map(|(kmer, cb)| (kmer, None, Some(cb))),
+                std::cmp::Ordering::Equal => a
+                    .next()
+                    .and_then(|(kmer, ca)| b.next().map(|(_, cb)| (kmer, Some(ca), Some(cb)))),
+            }
+        }))
+    }
+
+    /// Like [KmcFile::iter_kmers] but only the kmers starting with `prefix`.
+    ///
+    /// KMC's API cannot seek within a listing, so the entries before the first
+    /// match are read and skipped; as the listing is sorted, the iterator stops
+    /// right after the last match.
+    /// The listing of a data base of KMC 2 (see [KmcFile::signature_len]) is
+    /// not sorted, so there all remaining entries are read and filtered.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let tg = db.iter_prefix(&kmc_rs::Kmer::from("TG")?);
+    /// assert_eq!(tg.map(|(_, count)| count).sum::<usize>(), 13);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn iter_prefix(&mut self, prefix: &Kmer) -> impl Iterator<Item = (Kmer, usize)> + '_ {
+        let sorted = self.check_sorted().is_ok();
+        let prefix: Vec<u8> = prefix.codes().collect();
+        let n = prefix.len();
+        let start = prefix.clone();
+        let matches = move |kmer: &Kmer| kmer.codes().take(n).eq(prefix.iter().copied());
+        let keep = matches.clone();
+        self.iter_kmers()
+            .skip_while(move |(kmer, _)| sorted && kmer.codes().take(n).lt(start.iter().copied()))
+            .take_while(move |(kmer, _)| !sorted || matches(kmer))
+            .filter(move |(kmer, _)| sorted || keep(kmer))
+    }
+
+    /// Like [KmcFile::iter_kmers] but skip kmers whose count is not within `min..=max`.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// assert_eq!(db.iter_filtered(4, usize::MAX).count(), 70);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn iter_filtered(
+        &mut self,
+        min: usize,
+        max: usize,
+    ) -> impl Iterator<Item = (Kmer, usize)> + '_ {
+        self.iter_kmers()
+            .filter(move |&(_, count)| min <= count && count <= max)
+    }
+
+    /// Read all remaining entries into a map from kmer to count.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let counts = db.to_hashmap();
+    /// assert_eq!(counts[&kmc_rs::Kmer::from("TAAGA")?], 4);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn to_hashmap(&mut self) -> std::collections::HashMap<Kmer, usize> {
+        self.iter_kmers().collect()
+    }
+
+    /// Read up to `n` entries from the current position (fewer at the end of the file).
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let head = db.head(2);
+    /// assert_eq!(head[1], (kmc_rs::Kmer::from("AAAAC")?, 2));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn head(&mut self, n: usize) -> Vec<(Kmer, usize)> {
+        self.iter_kmers().take(n).collect()
+    }
+
+    /// Whether the remaining entries of both listings agree in all kmers and counts.
+    ///
+    /// The listing of a data base of KMC 2 (see [KmcFile::signature_len]) is
+    /// not sorted, so if either is one, the entries of both are collected and
+    /// sorted before comparing them.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// assert!(db.equals(&mut kmc_rs::KmcFile::open_iter("data/test1")?));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn equals(&mut self, other: &mut KmcFileIter) -> bool {
+        if self.kmer_length() != other.kmer_length() {
+            return false;
+        }
+        if self.check_sorted().is_ok() && other.check_sorted().is_ok() {
+            return self.iter_kmers().eq(other.iter_kmers());
+        }
+        let mut a: Vec<(Kmer, usize)> = self.iter_kmers().collect();
+        let mut b: Vec<(Kmer, usize)> = other.iter_kmers().collect();
+        a.sort_unstable();
+        b.sort_unstable();
+        a == b
+    }
+
+    /// Fold all remaining entries `(kmer, count)` into an accumulator.
+    ///
+    /// Only one [Kmer] is allocated, which is reused for all entries.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let gc = db.fold_entries(0, |gc, kmer, count| gc + kmer.gc_count() as usize * count);
+    /// assert!(gc > 0);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn fold_entries<B, F>(&mut self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, &Kmer, usize) -> B,
+    {
+        let mut acc = init;
+        let mut kmer = Kmer::with_len(self.kmer_length());
+        while let Some(count) = unsafe { self.read_next_unchecked(&mut kmer) } {
+            acc = f(acc, &kmer, count);
+        }
+        acc
+    }
+
+    /// Number of remaining entries `(kmer, count)` satisfying `pred`
+    /// (see [KmcFile::fold_entries]).
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// assert_eq!(db.count_where(|kmer, _| kmer.gc_count() == 0), 11);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn count_where<F>(&mut self, mut pred: F) -> usize
+    where
+        F: FnMut(&Kmer, usize) -> bool,
+    {
+        self.fold_entries(0, |n, kmer, count| n + pred(kmer, count) as usize)
+    }
+
+    /// Summarize the counts of all entries in one pass.
+    ///
+    /// The listing is [restarted](KmcFile::restart) before and after the pass;
+    /// fails with [KmcError::Kmc] if either restart fails.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let stats = db.count_stats()?;
+    /// assert_eq!((stats.distinct, stats.total, stats.max), (291, 852, 9));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn count_stats(&mut self) -> Result<CountStats, KmcError> {
+        self.try_restart()?;
+        let mut stats = CountStats {
+            total: 0,
+            distinct: 0,
+            max: 0,
+            mean: 0.0,
+        };
+        self.fold_entries(&mut stats, |stats, _, count| {
+            stats.total += count as u64;
+            stats.distinct += 1;
+            stats.max = stats.max.max(count);
+            stats
+        });
+        if stats.distinct > 0 {
+            stats.mean = stats.total as f64 / stats.distinct as f64;
+        }
+        self.try_restart()?;
+        Ok(stats)
+    }
+
+    /// Count spectrum of the remaining entries: index `i` holds the number of
+    /// kmers occurring `i` times, where counts above `max` are added to index `max`.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// assert_eq!(db.histogram(4), vec![0, 0, 136, 85, 70]);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn histogram(&mut self, max: usize) -> Vec<u64> {
+        self.fold_entries(vec![0; max + 1], |mut hist, _, count| {
+            hist[count.min(max)] += 1;
+            hist
+        })
+    }
+
+    /// Write the remaining entries as lines `kmer\tcount` to `out`, like `kmc_dump`.
+    ///
+    /// KMC lists the kmers of a data base in the format of KMC 1 in sorted order
+    /// already, but not those of KMC 2; if `sorted`, all entries are collected
+    /// and sorted before writing.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let mut out = Vec::new();
+    /// db.dump(&mut out, false)?;
+    /// assert!(out.starts_with(b"AAAAA\t2\nAAAAC\t2\n"));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn dump<W: std::io::Write>(&mut self, out: W, sorted: bool) -> Result<(), KmcError> {
+        use std::io::Write;
+
+        let mut out = std::io::BufWriter::new(out);
+        if sorted {
+            let mut entries: Vec<(String, usize)> = self.iter_strings().collect();
+            entries.sort_unstable();
+            for (kmer, count) in entries {
+                writeln!(out, "{}\t{}", kmer, count)?;
+            }
+        } else {
+            for (kmer, count) in self.iter_strings() {
+                writeln!(out, "{}\t{}", kmer, count)?;
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Iterate over all remaining entries with the kmers decoded to strings.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let first = db.iter_strings().next();
+    /// assert_eq!(first, Some(("AAAAA".to_string(), 2)));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn iter_strings(&mut self) -> impl Iterator<Item = (String, usize)> + '_ {
+        let mut kmer = Kmer::with_len(self.kmer_length());
+        std::iter::from_fn(move || {
+            unsafe { self.read_next_unchecked(&mut kmer) }.map(|count| (kmer.to_string(), count))
+        })
+    }
+
+    /// Read up to `min(kmers.len(), counts.len())` entries at once into `kmers`
+    /// (encoded like [Kmer::as_u64]) and `counts`; return the number of entries read,
+    /// which is smaller only at the end of the file.
+    ///
+    /// Fails like [Kmer::try_as_u64] if `k > 32`, as the kmers would not fit.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let (mut kmers, mut counts) = ([0; 64], [0; 64]);
+    /// let mut total = 0;
+    /// loop {
+    ///     let n = db.read_block(&mut kmers, &mut counts)?;
+    ///     total += counts[..n].iter().sum::<usize>();
+    ///     if n < kmers.len() {
+    ///         break;
+    ///     }
+    /// }
+    /// assert_eq!(total, 852);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn read_block(
+        &mut self,
+        kmers: &mut [u64],
+        counts: &mut [usize],
+    ) -> Result<usize, KmcError> {
+        let k = self.kmer_length();
+        if k > 32 {
+            return Err(KmcError::InvalidKmer(format!(
+                "a {}-mer does not fit into 64 bits",
+                k
+            )));
+        }
+        if self.stalled {
+            return Ok(0);
+        }
+        let n = self.ptr.pin_mut().read_block(kmers, counts);
+        self.listed += n;
+        Ok(n)
+    }
+
+    /// Reset the file pointer to the beginning, so the listing yields all entries
+    /// again, starting with the first one.
+    ///
+    /// If this fails (`false`), the listing yields no entries at all (rather than
+    /// continuing at an unknown position) until restarted successfully.
+    pub fn restart(&mut self) -> bool {
+        let restarted = self.ptr.pin_mut().restart_listing();
+        self.stalled = !restarted;
+        if restarted {
+            self.listed = 0;
+        }
+        restarted
+    }
+
+    /// Like [KmcFile::restart] but fail with [KmcError::Kmc] instead of returning `false`.
+    fn try_restart(&mut self) -> Result<(), KmcError> {
+        if self.restart() {
+            Ok(())
+        } else {
+            Err(KmcError::Kmc(format!(
+                "restart the listing of '{}'",
+                self.prefix
+            )))
+        }
+    }
+
+    /// Read next entry into `kmer`.
+    ///
+    /// If there was one available return `Some(count)`; otherwise
+    /// return None to indicate the end of the file
+    /// ([KmcFile::restart] might be useful then).
+    pub fn read_next(&mut self, kmer: &mut Kmer) -> Option<usize> {
+        if self.accepts(kmer) {
+            unsafe { self.read_next_unchecked(kmer) }
+        } else {
+            None
+        }
+    }
+
+    /// Like [KmcFile::read_next] but fail instead of returning garbage if the
+    /// data base is malformed, e.g. if its `.kmc_suf` file was truncated.
+    ///
+    /// Before the first entry, the size of the `.kmc_suf` file is compared to
+    /// the number of kmers in the header; afterwards, zero counters and a
+    /// listing that ends too early are reported.
+    /// ```rust
+    /// let mut db = kmc_rs::KmcFile::open_iter("data/test1")?;
+    /// let mut kmer = db.new_kmer_buffer();
+    /// assert_eq!(db.try_read_next(&mut kmer)?, Some(2));
+    /// assert_eq!(kmer.to_string(), "AAAAA");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn try_read_next(&mut self, kmer: &mut Kmer) -> Result<Option<usize>, KmcError> {
+        self.check_length(kmer)?;
+        if self.stalled {
+            return Ok(None);
+        }
+        if self.listed == 0 {
+            self.check_suffix_size()?;
+        }
+        let mut count = 0;
+        match self
+            .ptr
+            .pin_mut()
+            .try_next(kmer.handle.pin_mut(), &mut count)
+        {
+            Ok(true) => {
+                self.listed += 1;
+                Ok(Some(count))
+            }
+            Ok(false) if (self.listed as u64) < self.total_kmers() => {
+                Err(KmcError::Format(format!(
+                    "'{}' ended after {} of {} kmers",
+                    self.prefix,
+                    self.listed,
+                    self.total_kmers()
+                )))
+            }
+            Ok(false) => Ok(None),
+            Err(e) => Err(KmcError::Format(format!(
+                "'{}' after {} kmers: {}",
+                self.prefix,
+                self.listed,
+                e.what()
+            ))),
+        }
+    }
+
+    fn check_suffix_size(&self) -> Result<(), KmcError> {
+        let path = std::path::PathBuf::from(format!("{}.kmc_suf", self.prefix));
+        let actual = match std::fs::metadata(&path) {
+            Ok(meta) => meta.len(),
+            Err(source) => return Err(KmcError::MissingFile { path, source }),
+        };
+        let record_bytes =
+            (self.kmer_length() - self.lut_prefix_length()) / 4 + self.counter_size();
+        // both markers "KMCS" and the records
+        let expected = 8 + self.total_kmers() * record_bytes as u64;
+        if actual != expected {
+            return Err(KmcError::Format(format!(
+                "{} has {} bytes but {} kmers need {}",
+                path.display(),
+                actual,
+                self.total_kmers(),
+                expected
+            )));
+        }
+        Ok(())
+    }
+
+    /// Like [KmcFile::read_next] but do not check the lengths.
+    ///
+    /// # Safety
+    /// Might crash when `self.kmer_length() != kmer.len()`.
+    pub unsafe fn read_next_unchecked(&mut self, kmer: &mut Kmer) -> Option<usize> {
+        let mut count = 0;
+        if self.stalled {
+            return None;
+        }
+        if self.ptr.pin_mut().next(kmer.handle.pin_mut(), &mut count) {
+            self.listed += 1;
+            Some(count)
+        } else {
+            None
+        }
+    }
+}
+
+// SAFETY: `CKMCFile` owns its file handles and buffers and does not depend on
+// the thread it was created by.
+unsafe impl<M> Send for KmcFile<M> {}
+
+// SAFETY: When opened for random access, KMC loads the whole data base into
+// memory; afterwards, queries via `&self` (`CheckKmer`, `GetCountersForRead`)
+// only read these buffers. Hence, concurrent queries do not race.
+unsafe impl Sync for KmcFileRa {}
+
+impl<M> Drop for KmcFile<M> {
+    fn drop(&mut self) {
+        // the pointer is null after reopening
+        if !self.ptr.is_null() && !self.ptr.pin_mut().close() {
+            panic!("error while closing");
+        }
+    }
+}
+
+impl<'a> Iterator for KmcFileIterU64<'a> {
+    type Item = (u64, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.file
+                .read_next_unchecked(&mut self.kmer)
+                .map(|c| (self.kmer.as_u64(), c))
+        }
+    }
+
+    /// Exact, as the header records the number of kmers; none remain after a
+    /// failed [restart](KmcFile::restart).
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.file.stalled {
+            return (0, Some(0));
+        }
+        let remaining = self.file.num_kmers().saturating_sub(self.file.listed);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for KmcFileIterU64<'a> {}
+
+impl<'a> KmcCursor<'a> {
+    /// Move to the next entry; return `false` at the end of the file.
+    pub fn advance(&mut self) -> bool {
+        match unsafe { self.file.read_next_unchecked(&mut self.kmer) } {
+            Some(count) => {
+                self.count = count;
+                true
+            }
+            None => {
+                self.count = 0;
+                false
+            }
+        }
+    }
+
+    /// The kmer of the current entry.
+    ///
+    /// Only meaningful after [KmcCursor::advance] returned `true`.
+    pub fn current_kmer(&self) -> &Kmer {
+        &self.kmer
+    }
+
+    /// The count of the current entry; `0` before the first and after the last entry.
+    pub fn current_count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Kmer {
+    /// Construct a kmer by a `&str` of the symbols `ACGT` in upper or lower case.
+    ///
+    /// Leading and trailing ASCII whitespace (e.g. a newline) is ignored;
+    /// whitespace within the kmer is an error.
+    /// ```rust
+    /// assert_eq!(kmc_rs::Kmer::from(" taAGa\n")?.to_string(), "TAAGA");
+    /// assert!(kmc_rs::Kmer::from("TA AGA").is_err());
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn from(kmer: &str) -> Result<Self, KmcError> {
+        let kmer = kmer.trim_matches(|c: char| c.is_ascii_whitespace());
+        let mut handle = cxxbridge::ffi::new_kmerapi();
+        if !handle.pin_mut().from_string(kmer) {
+            return Err(KmcError::InvalidKmer(kmer.to_string()));
+        }
+        Ok(Self { handle })
+    }
+
+    /// Construct a kmer from ASCII symbols `ACGT` (upper or lower case),
+    /// e.g. taken directly from a FASTQ record without UTF-8 validation.
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from_ascii(b"TaaGA")?;
+    /// assert_eq!(kmer.to_string(), "TAAGA");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn from_ascii(seq: &[u8]) -> Result<Self, KmcError> {
+        if seq.iter().any(|&b| base_to_code(b).is_none()) {
+            return Err(KmcError::InvalidKmer(
+                String::from_utf8_lossy(seq).into_owned(),
+            ));
+        }
+        Ok(Self::from_codes(
+            seq.len() as u32,
+            seq.iter().filter_map(|&b| base_to_code(b)),
+        ))
+    }
+
+    /// Construct a new kmer and reserve space for `k` symbols
+    /// (see [Kmer::with_len] for `k > 255`).
+    ///
+    /// For `k == 0` this is the empty kmer: it has no rows, [Kmer::as_u64] is `0`,
+    /// its string is `""` and no data base contains it.
+    pub fn with_k(k: u8) -> Self {
+        Self {
+            handle: cxxbridge::ffi::new_kmerapi_with_len(k as u32),
+        }
+    }
+
+    /// Number of symbols `k` of this kmer.
+    pub fn len(&self) -> u32 {
+        self.handle.kmer_len()
+    }
+
+    /// Same as [Kmer::len] but as `usize`, e.g. for indexing.
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.len() as usize
+    }
+
+    /// Construct a kmer from bit encoded kmer `val` with `k` symbols.
+    /// Note: `k` must be at most `32`!
+    /// See [Kmer::set_u64] for further details.
+    pub fn from_u64(k: u8, val: u64) -> Self {
+        let mut kmer = Self::with_k(k);
+        kmer.set_u64(val);
+        kmer
+    }
+
+    /// Like [Kmer::from_u64] but fail if `k > 32` or if `val` has bits set
+    /// beyond the lowest `2 * k` (see [Kmer::try_set_u64]).
+    /// ```rust
+    /// assert!(kmc_rs::Kmer::try_from_u64(2, 0b11_00_00_10_00).is_err());
+    /// assert_eq!(kmc_rs::Kmer::try_from_u64(2, 0b10_00)?.to_string(), "GA");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn try_from_u64(k: u8, val: u64) -> Result<Self, KmcError> {
+        let mut kmer = Self::with_k(k);
+        kmer.try_set_u64(val)?;
+        Ok(kmer)
+    }
+
+    /// Reset the kmer to a new bit encoded kmer of same length.
+    /// Note: length `k` must be at most `32`!
+    ///
+    /// The coding is as usual (see [encoding]):
+    ///  - `A` -> `0b00`
+    ///  - `C` -> `0b01`
+    ///  - `G` -> `0b10`
+    ///  - `T` -> `0b11`
+    ///
+    /// # Example
+    /// ```rust
+    /// let mut kmer = kmc_rs::Kmer::with_k(5);
+    /// kmer.set_u64(0b11_00_00_10_00);
+    /// assert_eq!(kmer.to_string(), "TAAGA");
+    /// ```
+    #[inline]
+    pub fn set_u64(&mut self, val: u64) {
+        debug_assert!(self.len() <= 32);
+        self.handle.pin_mut().set_u64(val);
+    }
+
+    /// Like [Kmer::set_u64] but fail if `self.len() > 32` or if `val` has bits
+    /// set beyond the lowest `2 * self.len()`; then `self` is unchanged.
+    /// ```rust
+    /// let mut kmer = kmc_rs::Kmer::with_k(2);
+    /// assert!(kmer.try_set_u64(0b11_00_00_10_00).is_err());
+    /// kmer.try_set_u64(0b10_00)?;
+    /// assert_eq!(kmer.to_string(), "GA");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn try_set_u64(&mut self, val: u64) -> Result<(), KmcError> {
+        let k = self.len();
+        if k > 32 {
+            return Err(KmcError::InvalidKmer(format!(
+                "a {}-mer does not fit into 64 bits",
+                k
+            )));
+        }
+        if k < 32 && val >> (2 * k) != 0 {
+            return Err(KmcError::InvalidKmer(format!(
+                "{:#b} does not encode a {}-mer",
+                val, k
+            )));
+        }
+        self.set_u64(val);
+        Ok(())
+    }
+
+    /// Obtain the first 64 bits of this Kmer.
+    /// When `self.len() > 32` the result is truncated to the first `32` symbols
+    /// (see [Kmer::try_as_u64]).
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("TAAGA")?;
+    /// assert_eq!(kmer.as_u64(), 0b11_00_00_10_00);
+    /// Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    #[inline]
+    pub fn as_u64(&self) -> u64 {
+        self.handle.as_u64()
+    }
+
+    /// Like [Kmer::as_u64] but fail instead of truncating if `self.len() > 32`.
+    pub fn try_as_u64(&self) -> Result<u64, KmcError> {
+        if self.len() > 32 {
+            return Err(KmcError::InvalidKmer(format!(
+                "a {}-mer does not fit into 64 bits",
+                self.len()
+            )));
+        }
+        Ok(self.as_u64())
+    }
+
+    /// Like [Kmer::from_u64] but for up to `64` symbols.
+    /// See [Kmer::set_u128] for the bit layout.
+    pub fn from_u128(k: u8, val: u128) -> Self {
+        let mut kmer = Self::with_k(k);
+        kmer.set_u128(val);
+        kmer
+    }
+
+    /// Reset the kmer to a new bit encoded kmer of same length.
+    /// Note: length `k` must be at most `64`!
+    ///
+    /// The coding is the same as in [Kmer::set_u64]: the first symbol occupies
+    /// the most significant of the lower `2 * k` bits, the last symbol the two
+    /// least significant bits.
+    /// ```rust
+    /// let mut kmer = kmc_rs::Kmer::with_k(5);
+    /// kmer.set_u128(0b11_00_00_10_00);
+    /// assert_eq!(kmer.to_string(), "TAAGA");
+    /// ```
+    pub fn set_u128(&mut self, val: u128) {
+        debug_assert!(self.len() <= 64);
+        let k = self.len();
+        self.set_codes((0..k).rev().map(|i| ((val >> (2 * i)) & 0b11) as u8));
+    }
+
+    /// Obtain the first 128 bits of this Kmer.
+    /// When `self.len() > 64` the bits are incomplete.
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("TAAGA")?;
+    /// assert_eq!(kmer.as_u128(), 0b11_00_00_10_00);
+    /// Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn as_u128(&self) -> u128 {
+        self.codes()
+            .take(64)
+            .fold(0, |val, c| (val << 2) | c as u128)
+    }
+
+    /// Construct a kmer with `k` symbols from its packed representation
+    /// as returned by [Kmer::as_bytes].
+    /// Fails if `bytes` does not consist of exactly `(k + 3) / 4` bytes.
+    pub fn from_bytes(k: u32, bytes: &[u8]) -> Result<Self, KmcError> {
+        let k = k as usize;
+        if bytes.len() != k.div_ceil(4) {
+            return Err(KmcError::InvalidKmer(format!(
+                "expected {} bytes for a {}-mer, got {}",
+                k.div_ceil(4),
+                k,
+                bytes.len()
+            )));
+        }
+        let pad = (4 - k % 4) % 4;
+        Ok(Self::from_codes(
+            k as u32,
+            (pad..pad + k).map(|p| (bytes[p / 4] >> (6 - 2 * (p % 4))) & 0b11),
+        ))
+    }
+
+    /// All symbols packed into `(k + 3) / 4` bytes, four symbols per byte.
+    ///
+    /// Like KMC does internally, the first byte is padded by leading zero bits
+    /// such that the last symbol ends up in the two least significant bits
+    /// of the last byte.
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("TAAGA")?;
+    /// assert_eq!(kmer.as_bytes(), vec![0b00_00_00_11, 0b00_00_10_00]);
+    /// Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let k = self.len() as usize;
+        let pad = (4 - k % 4) % 4;
+        let mut bytes = vec![0; k.div_ceil(4)];
+        for (p, c) in (pad..).zip(self.codes()) {
+            bytes[p / 4] |= c << (6 - 2 * (p % 4));
+        }
+        bytes
+    }
+
+    /// Whether this kmer has no symbols at all, i.e. `k == 0`.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reverse complement, i.e. the kmer read on the other strand.
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("TAAGA")?;
+    /// assert_eq!(kmer.reverse_complement().to_string(), "TCTTA");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn reverse_complement(&self) -> Self {
+        Self::from_codes(self.len(), self.codes().rev().map(|c| 0b11 - c))
+    }
+
+    /// Drop the first symbol and append the symbol with 2-bit code `next_base`
+    /// (see [Kmer::set_u64]), so the length stays the same.
+    /// Useful to slide a window over a read:
+    /// ```rust
+    /// let mut kmer = kmc_rs::Kmer::from("TAAGA")?;
+    /// kmer.roll(0b01);
+    /// assert_eq!(kmer.to_string(), "AAGAC");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn roll(&mut self, next_base: u8) {
+        let k = self.len();
+        if k == 0 {
+            return;
+        }
+        let offset = self.handle.byte_align();
+        let data = self.handle.pin_mut().data_mut();
+        for i in 0..data.len() {
+            let carry = data.get(i + 1).map_or(0, |next| next >> 62);
+            data[i] = (data[i] << 2) | carry;
+        }
+        data[0] &= !0 >> (2 * offset);
+        let p = offset + k - 1;
+        data[(p / 32) as usize] |= (next_base as u64 & 0b11) << (62 - 2 * (p % 32));
+    }
+
+    /// Complement every symbol (`A` ↔ `T`, `C` ↔ `G`) but keep their order.
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("ACGT")?;
+    /// assert_eq!(kmer.complement().to_string(), "TGCA");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn complement(&self) -> Self {
+        Self::from_codes(self.len(), self.codes().map(|c| 0b11 - c))
+    }
+
+    /// Reverse the order of the symbols (without complementing them).
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("TAAGA")?;
+    /// assert_eq!(kmer.reverse().to_string(), "AGAAT");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn reverse(&self) -> Self {
+        Self::from_codes(self.len(), self.codes().rev())
+    }
+
+    /// Canonical form: the smaller of this kmer and its [reverse complement].
+    ///
+    /// By default KMC counts both strands and stores only canonical kmers,
+    /// but [KmcFile::count_kmer] looks up the kmer exactly as given.
+    /// So, unless the data base was built with `-b` (not counting both strands),
+    /// query the canonical form:
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// let kmer = kmc_rs::Kmer::from("TCTTA")?;
+    /// assert_eq!(db.count_kmer(&kmer.canonical()), 4);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    ///
+    /// [reverse complement]: Kmer::reverse_complement
+    pub fn canonical(&self) -> Self {
+        let rc = self.reverse_complement();
+        if rc.handle.data() < self.handle.data() {
+            rc
+        } else {
+            Self::from_codes(self.len(), self.codes())
+        }
+    }
+
+    /// Same as `self.canonical().as_u64()` but without allocating another kmer;
+    /// see [encoding::canonical_u64].
+    /// Note: `self.len()` must be at most `32`!
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("TCTTA")?;
+    /// assert_eq!(kmer.canonical_u64(), kmc_rs::Kmer::from("TAAGA")?.as_u64());
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn canonical_u64(&self) -> u64 {
+        debug_assert!(self.len() <= 32);
+        encoding::canonical_u64(self.len() as u8, self.as_u64())
+    }
+
+    /// Whether this kmer is its own [canonical form](Kmer::canonical).
+    pub fn is_canonical(&self) -> bool {
+        self.handle.data() <= self.reverse_complement().handle.data()
+    }
+
+    /// The 2-bit code (`0..=3`, see [Kmer::set_u64]) of the symbol at position `pos`
+    /// or `None` if `pos >= self.len()`.
+    pub fn base_at(&self, pos: u32) -> Option<u8> {
+        if pos < self.len() {
+            Some(self.code(pos))
+        } else {
+            None
+        }
+    }
+
+    /// The symbol (one of `ACGT`) at position `pos` or `None` if `pos >= self.len()`.
+    pub fn base_char_at(&self, pos: u32) -> Option<char> {
+        self.base_at(pos).map(|c| code_to_base(c) as char)
+    }
+
+    /// The 2-bit codes (see [encoding]) of all symbols, starting with the first one.
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("TAAGA")?;
+    /// assert_eq!(kmer.bases().collect::<Vec<_>>(), vec![3, 0, 0, 2, 0]);
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn bases(&self) -> impl DoubleEndedIterator<Item = u8> + '_ {
+        self.codes()
+    }
+
+    /// The symbols (each one of `ACGT`), starting with the first one.
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("TAAGA")?;
+    /// assert_eq!(kmer.chars().rev().collect::<String>(), "AGAAT");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn chars(&self) -> impl DoubleEndedIterator<Item = char> + '_ {
+        self.codes().map(|c| code_to_base(c) as char)
+    }
+
+    /// The symbols in the given letter `case`.
+    /// ```rust
+    /// use kmc_rs::{Case, Kmer};
+    ///
+    /// let kmer = Kmer::from("TAAGA")?;
+    /// assert_eq!(kmer.format_with(Case::Lower), "taaga");
+    /// assert_eq!(kmer.format_with(Case::Upper), kmer.to_string());
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn format_with(&self, case: Case) -> String {
+        let symbols = match case {
+            Case::Upper => b"ACGT",
+            Case::Lower => b"acgt",
+        };
+        self.codes().map(|c| symbols[c as usize] as char).collect()
+    }
+
+    /// The symbols in lower case, see [Kmer::format_with].
+    pub fn to_string_lowercase(&self) -> String {
+        self.format_with(Case::Lower)
+    }
+
+    /// Number of `C` and `G` symbols.
+    pub fn gc_count(&self) -> u32 {
+        self.codes().filter(|&c| c == 0b01 || c == 0b10).count() as u32
+    }
+
+    /// Fraction of `C` and `G` symbols (`0.0` for an empty kmer).
+    pub fn gc_fraction(&self) -> f64 {
+        if self.is_empty() {
+            0.0
+        } else {
+            self.gc_count() as f64 / self.len() as f64
+        }
+    }
+
+    /// Number of positions with different symbols;
+    /// `None` if the lengths differ.
+    pub fn hamming_distance(&self, other: &Kmer) -> Option<u32> {
+        if self.len() == other.len() {
+            Some(
+                self.codes()
+                    .zip(other.codes())
+                    .filter(|(a, b)| a != b)
+                    .count() as u32,
+            )
+        } else {
+            None
+        }
+    }
+
+    /// All `3 * k` kmers with [Hamming distance](Kmer::hamming_distance) `1`,
+    /// i.e. differing from this one in exactly one symbol.
+    pub fn neighbors_hamming1(&self) -> Vec<Kmer> {
+        let codes: Vec<u8> = self.codes().collect();
+        let mut neighbors = Vec::with_capacity(3 * codes.len());
+        for (pos, &code) in codes.iter().enumerate() {
+            for other in (0..4).filter(|&c| c != code) {
+                let substituted = codes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &c)| if i == pos { other } else { c });
+                neighbors.push(Self::from_codes(self.len(), substituted));
+            }
+        }
+        neighbors
+    }
+
+    /// The smallest (see [Ord]) of all sub-kmers of length `m` together
+    /// with its start position (the first one in case of ties).
+    /// `None` if `m == 0` or `m > self.len()`.
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("TGACGTAC")?;
+    /// let (pos, minimizer) = kmer.minimizer(4).unwrap();
+    /// assert_eq!((pos, minimizer.to_string()), (2, "ACGT".to_string()));
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn minimizer(&self, m: u32) -> Option<(u32, Kmer)> {
+        self.min_window(m, |w| w)
+    }
+
+    /// Like [Kmer::minimizer] but considering both strands, i.e. comparing
+    /// the [canonical](Kmer::canonical) forms of the sub-kmers.
+    pub fn canonical_minimizer(&self, m: u32) -> Option<(u32, Kmer)> {
+        self.min_window(m, |w| w.canonical())
+    }
+
+    fn min_window<F: Fn(Kmer) -> Kmer>(&self, m: u32, f: F) -> Option<(u32, Kmer)> {
+        if m == 0 || m > self.len() {
+            return None;
+        }
+        (0..=self.len() - m)
+            .map(|start| (start, f(self.sub(start, m))))
+            .min_by(|(_, a), (_, b)| a.cmp(b))
+    }
+
+    /// The sub-kmer of length `len` starting at position `start`
+    /// or `None` if it does not fit into this kmer.
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("TAAGA")?;
+    /// assert_eq!(kmer.slice(1, 3).unwrap().to_string(), "AAG");
+    /// assert!(kmer.slice(3, 3).is_none());
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn slice(&self, start: u32, len: u32) -> Option<Kmer> {
+        if start.checked_add(len)? <= self.len() {
+            Some(self.sub(start, len))
+        } else {
+            None
+        }
+    }
+
+    /// The first `k - 1` symbols (empty if this kmer is empty).
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("ACGT")?;
+    /// assert_eq!(kmer.prefix().to_string(), "ACG");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn prefix(&self) -> Kmer {
+        self.sub(0, self.len().saturating_sub(1))
+    }
+
+    /// The last `k - 1` symbols (empty if this kmer is empty).
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("ACGT")?;
+    /// assert_eq!(kmer.suffix().to_string(), "CGT");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn suffix(&self) -> Kmer {
+        self.sub(self.len().min(1), self.len().saturating_sub(1))
+    }
+
+    /// A new kmer of length `k + 1` with the symbol of 2-bit code `base`
+    /// (see [Kmer::set_u64]) appended; unlike [Kmer::roll], the length grows.
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("ACG")?;
+    /// assert_eq!(kmer.append_base(0b11).to_string(), "ACGT");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn append_base(&self, base: u8) -> Kmer {
+        Self::from_codes(
+            self.len() + 1,
+            self.codes().chain(std::iter::once(base & 0b11)),
+        )
+    }
+
+    /// A new kmer of length `k + 1` with the symbol of 2-bit code `base` prepended.
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::from("ACG")?;
+    /// assert_eq!(kmer.prepend_base(0b00).to_string(), "AACG");
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn prepend_base(&self, base: u8) -> Kmer {
+        Self::from_codes(
+            self.len() + 1,
+            std::iter::once(base & 0b11).chain(self.codes()),
+        )
+    }
+
+    /// The `len` symbols starting at `start` (no bounds checks).
+    fn sub(&self, start: u32, len: u32) -> Kmer {
+        Self::from_codes(len, self.codes().skip(start as usize))
+    }
+
+    /// Construct a kmer with `k` symbols, e.g. up to [MAX_K]
+    /// (not limited to `u8` like [Kmer::with_k]).
+    /// ```rust
+    /// let kmer = kmc_rs::Kmer::with_len(kmc_rs::MAX_K);
+    /// assert_eq!(kmer.to_string(), "A".repeat(256));
+    /// ```
+    pub fn with_len(k: u32) -> Self {
+        Self {
+            handle: cxxbridge::ffi::new_kmerapi_with_len(k),
+        }
+    }
+
+    /// The 2-bit codes of all symbols, starting with the first one.
+    ///
+    /// KMC stores the symbols in 64-bit rows, most significant bits first,
+    /// preceded by `byte_align()` zero symbols.
+    fn codes(&self) -> impl DoubleEndedIterator<Item = u8> + '_ {
+        let data = self.handle.data();
+        let offset = self.handle.byte_align();
+        (offset..offset + self.len())
+            .map(move |p| ((data[(p / 32) as usize] >> (62 - 2 * (p % 32))) & 0b11) as u8)
+    }
+
+    /// The 2-bit code of the symbol at `pos < self.len()`.
+    fn code(&self, pos: u32) -> u8 {
+        let p = self.handle.byte_align() + pos;
+        ((self.handle.data()[(p / 32) as usize] >> (62 - 2 * (p % 32))) & 0b11) as u8
+    }
+
+    /// Inverse of [Kmer::codes]: construct a kmer of length `k` from its 2-bit codes.
+    fn from_codes<I: IntoIterator<Item = u8>>(k: u32, codes: I) -> Self {
+        let mut kmer = Self::with_len(k);
+        kmer.set_codes(codes);
+        kmer
+    }
+
+    /// Overwrite all symbols by the 2-bit `codes`, starting with the first one.
+    fn set_codes<I: IntoIterator<Item = u8>>(&mut self, codes: I) {
+        let k = self.len();
+        let offset = self.handle.byte_align();
+        let data = self.handle.pin_mut().data_mut();
+        data.iter_mut().for_each(|row| *row = 0);
+        for (p, c) in (offset..offset + k).zip(codes) {
+            data[(p / 32) as usize] |= (c as u64 & 0b11) << (62 - 2 * (p % 32));
+        }
+    }
+}
+
+// SAFETY: `CKmerAPI` only owns the heap allocated encoding.
+unsafe impl Send for Kmer {}
+
+// SAFETY: A shared `&Kmer` only allows to read the encoding.
+unsafe impl Sync for Kmer {}
+
+/// Allocates a new encoding, so the clone is independent of the original.
+impl Clone for Kmer {
+    fn clone(&self) -> Self {
+        let mut kmer = Self::with_len(self.len());
+        kmer.handle
+            .pin_mut()
+            .data_mut()
+            .copy_from_slice(self.handle.data());
+        kmer
+    }
+}
+
+/// Two kmers are equal if they have the same length and encode the same symbols.
+impl PartialEq for Kmer {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.handle.data() == other.handle.data()
+    }
+}
+
+impl Eq for Kmer {}
+
+/// Lexicographic order of the symbols.
+///
+/// As KMC encodes `A < C < G < T`, this is the alphabetical order of the
+/// sequences, which is also the order KMC lists its kmers in.
+/// A proper prefix is smaller than the longer kmer.
+impl Ord for Kmer {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        if self.len() == other.len() {
+            self.handle.data().cmp(other.handle.data())
+        } else {
+            self.codes().cmp(other.codes())
+        }
+    }
+}
+
+impl PartialOrd for Kmer {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Consistent with [PartialEq]: hashes the length and the complete encoding.
+impl std::hash::Hash for Kmer {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        self.handle.data().hash(state);
+    }
+}
+
+impl std::fmt::Display for cxxbridge::ffi::Kmer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.to_string())
+    }
+}
+
+impl std::fmt::Display for Kmer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.handle.fmt(f)
+    }
+}
+
+/// Same as [Kmer::from], e.g.
+/// ```rust
+/// let kmer: kmc_rs::Kmer = "TAAGA".parse()?;
+/// assert_eq!(kmer.len(), 5);
+/// # Ok::<(), kmc_rs::KmcError>(())
+/// ```
+impl std::str::FromStr for Kmer {
+    type Err = KmcError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from(s)
+    }
+}
+
+/// Same as [Kmer::from_ascii].
+impl std::convert::TryFrom<&[u8]> for Kmer {
+    type Error = KmcError;
+
+    fn try_from(seq: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_ascii(seq)
+    }
+}
+
+/// Same as [Kmer::try_from_u64]; a `u64` alone does not determine `k`.
+/// ```rust
+/// use std::convert::TryFrom;
+///
+/// let kmer = kmc_rs::Kmer::try_from((5, 0b11_00_00_10_00))?;
+/// assert_eq!(kmer.to_string(), "TAAGA");
+/// assert!(kmc_rs::Kmer::try_from((2, 0b11_00_00_10_00)).is_err());
+/// # Ok::<(), kmc_rs::KmcError>(())
+/// ```
+impl std::convert::TryFrom<(u8, u64)> for Kmer {
+    type Error = KmcError;
+
+    fn try_from((k, val): (u8, u64)) -> Result<Self, Self::Error> {
+        Self::try_from_u64(k, val)
+    }
+}
+
+/// Shows the symbols and the length, e.g. `Kmer("TAAGA", k=5)`.
+impl std::fmt::Debug for Kmer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Kmer({:?}, k={})", self.to_string(), self.len())
+    }
+}
+
+impl<M: mode::Mode> std::fmt::Debug for KmcFile<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("KmcFile")
+            .field("k", &self.kmer_length())
+            .field("mode", &M::MODE)
+            .finish()
+    }
+}
+
+/// The common prefix of the files of the data base `path`, i.e. without a suffix
+/// `.kmc_pre` or `.kmc_suf`.
+fn db_prefix(path: &Path) -> Result<&str, KmcError> {
+    let fname = path
+        .to_str()
+        .ok_or_else(|| KmcError::InvalidPath(path.to_path_buf()))?;
+    Ok(fname
+        .strip_suffix(".kmc_pre")
+        .or_else(|| fname.strip_suffix(".kmc_suf"))
+        .unwrap_or(fname))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_kmc2, TempDir};
+
+    #[test]
+    fn test_open() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.kmer_length(), 5);
+        assert_eq!(io.num_kmers(), 291);
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_error() {
+        match KmcFile::open_ra("./data/missing") {
+            Err(KmcError::MissingFile { path, .. }) => {
+                assert_eq!(path, std::path::Path::new("./data/missing.kmc_pre"))
+            }
+            _ => panic!("expected KmcError::MissingFile"),
+        }
+        match KmcFile::open_ra("./src/lib.rs") {
+            Err(e @ KmcError::MissingFile { .. }) => {
+                assert!(e
+                    .to_string()
+                    .starts_with("Cannot read './src/lib.rs.kmc_pre'"))
+            }
+            _ => panic!("expected KmcError::MissingFile"),
+        }
+    }
+
+    #[test]
+    fn test_open_missing_suffix_file() -> Result<(), KmcError> {
+        let dir = TempDir::new("missing")?;
+        std::fs::copy("./data/test1.kmc_pre", dir.join("test1.kmc_pre"))?;
+        let missing = KmcFile::open_iter(dir.join("test1"));
+        std::fs::write(dir.join("test1.kmc_suf"), b"garbage")?;
+        let broken = KmcFile::open_ra(dir.join("test1"));
+        match missing {
+            Err(KmcError::MissingFile { path, .. }) => {
+                assert_eq!(path, dir.join("test1.kmc_suf"))
+            }
+            _ => panic!("expected KmcError::MissingFile"),
+        }
+        match broken {
+            Err(e @ KmcError::OpenFailed { .. }) => assert_eq!(
+                e.to_string(),
+                format!(
+                    "Could not open '{}' in random access mode: not a KMC data base",
+                    dir.join("test1").display()
+                )
+            ),
+            _ => panic!("expected KmcError::OpenFailed"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_reopen() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.count_str("TAAGA")?, 4);
+        let mut io = io.reopen_as_iter()?;
+        assert_eq!(io.kmer_length(), 5);
+        let listed: Vec<(u64, usize)> = io.iter_u64().collect();
+        assert_eq!(listed.len(), 291);
+        assert_eq!(
+            listed,
+            KmcFile::open_iter("./data/test1")?
+                .iter_u64()
+                .collect::<Vec<_>>()
+        );
+
+        let io = io.reopen_as_ra()?;
+        assert_eq!(io.count_str("TAAGA")?, 4);
+        assert!(io.try_count_kmer(&Kmer::from("TAAG")?).is_err());
+        let mut io = io.reopen_as_iter()?;
+        assert_eq!(io.head(1)[0].0.to_string(), "AAAAA");
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(KmcFile::is_valid("./data/test1"));
+        assert!(KmcFile::is_valid(std::path::Path::new(
+            "./data/test1.kmc_suf"
+        )));
+        assert!(!KmcFile::is_valid("./data/bogus"));
+        assert!(!KmcFile::is_valid("./src/lib.rs"));
+    }
+
+    #[test]
+    fn test_open_path() -> Result<(), KmcError> {
+        let path = std::path::PathBuf::from("./data").join("test1");
+        assert_eq!(KmcFile::open_ra(&path)?.kmer_length(), 5);
+        assert_eq!(KmcFile::open_iter(path)?.kmer_length(), 5);
+        let io = KmcFile::open_ra("./data/test1.kmc_pre")?;
+        assert_eq!(io.count_str("TAAGA")?, 4);
+        assert_eq!(KmcFile::open_iter("./data/test1.kmc_suf")?.num_kmers(), 291);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_non_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::ffi::OsStr::from_bytes(b"./data/test\xff");
+        assert!(matches!(
+            KmcFile::open_ra(path),
+            Err(KmcError::InvalidPath(_))
+        ));
+    }
+
+    #[test]
+    fn test_min_max_count() -> Result<(), KmcError> {
+        // data/test1 was built with `kmc -k5 -ci2` (and the default `-cx1e9`)
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.min_count(), 2);
+        assert_eq!(io.max_count(), 1_000_000_000);
+        let io = KmcFile::open_iter("./data/test1")?;
+        assert_eq!(io.min_count(), 2);
+        assert_eq!(io.max_count(), 1_000_000_000);
+        Ok(())
+    }
+
+    #[test]
+    fn test_make_kmer() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.count_kmer(&io.make_kmer("TAAGA")?), 4);
+        assert!(matches!(
+            io.make_kmer("TAAG"),
+            Err(KmcError::LengthMismatch {
+                expected: 5,
+                got: 4
+            })
+        ));
+        assert!(matches!(
+            io.make_kmer("TAXGA"),
+            Err(KmcError::InvalidKmer(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_count_kmer() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.try_count_kmer(&Kmer::from("TAAGA")?)?, 4);
+        assert_eq!(io.try_count_kmer(&Kmer::from("TCTTA")?)?, 0);
+        assert!(matches!(
+            io.try_count_kmer(&Kmer::from("TAAGACG")?),
+            Err(KmcError::LengthMismatch {
+                expected: 5,
+                got: 7
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mode() -> Result<(), KmcError> {
+        assert_eq!(
+            KmcFile::open_ra("./data/test1")?.mode(),
+            KmcMode::Occurrence
+        );
+        assert_eq!(
+            KmcFile::open_iter("./data/test1")?.mode(),
+            KmcMode::Occurrence
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_contains() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert!(io.contains(&Kmer::from("TAAGA")?)?);
+        assert!(!io.contains(&Kmer::from("TCTTA")?)?);
+        assert!(!io.contains(&Kmer::from("AAAAG")?)?);
+        assert!(io.contains(&Kmer::from("TAAG")?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_coverage() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        for read in &["TCTTAAGACG", "TCTTANGACGAC", "AAAAGAAA", "TCT"] {
+            let sum: usize = io.counts_for_read(read)?.iter().sum();
+            assert_eq!(io.read_coverage(read)?, sum);
+        }
+        assert_eq!(io.read_coverage("TCTTANGACGAC")?, 7);
+        assert_eq!(io.read_coverage("AAAAGAAA")?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_hit_fraction() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.read_hit_fraction("TCTTAAGAC")?, 1.0);
+        assert_eq!(io.read_hit_fraction("AAAAGAAA")?, 0.0);
+        assert_eq!(io.read_hit_fraction("TCTTANGACGAC")?, 0.25);
+        assert_eq!(io.read_hit_fraction("TCTT")?, 0.0);
+        assert_eq!(io.read_hit_fraction("")?, 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_counts_for_read_strided() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        let read = "TCTTAAGACG";
+        assert_eq!(
+            io.counts_for_read_strided(read, 1)?,
+            io.counts_for_read(read)?
+        );
+        assert_eq!(io.counts_for_read_strided(read, 2)?, vec![4, 2, 2]);
+        assert_eq!(io.counts_for_read_strided(read, 4)?, vec![4, 2]);
+        assert_eq!(io.counts_for_read_strided(read, 10)?, vec![4]);
+        assert!(io.counts_for_read_strided("TCTT", 2)?.is_empty());
+        for read in &["TCTTAnGACGTAAGA", "tcttaagacgt", "GGTCTTAAGAN"] {
+            for stride in 1..4 {
+                let all: Vec<usize> = io
+                    .counts_for_read(read)?
+                    .into_iter()
+                    .step_by(stride)
+                    .collect();
+                assert_eq!(io.counts_for_read_strided(read, stride)?, all, "{}", read);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "stride must be positive")]
+    fn test_counts_for_read_zero_stride() {
+        let io = KmcFile::open_ra("./data/test1").unwrap();
+        let _ = io.counts_for_read_strided("TCTTAAGACG", 0);
+    }
+
+    #[test]
+    fn test_counts_for_read_masked() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(
+            io.counts_for_read_masked("TCTTANGACGAC")?,
+            vec![Some(4), None, None, None, None, None, Some(0), Some(3)]
+        );
+        assert_eq!(
+            io.counts_for_read_masked("tcttaagacg")?,
+            vec![Some(4), Some(2), Some(2), Some(4), Some(2), Some(0)]
+        );
+        assert_eq!(io.counts_for_read_masked("NTCTTA")?, vec![None, Some(4)]);
+        assert!(io.counts_for_read_masked("TCT")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_counter_size() -> Result<(), KmcError> {
+        assert_eq!(KmcFile::open_ra("./data/test1")?.counter_size(), 1);
+        assert_eq!(KmcFile::open_iter("./data/test1")?.counter_size(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_layout() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.lut_prefix_length(), 1);
+        assert_eq!(io.signature_len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimated_ram_bytes() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        let bytes = io.estimated_ram_bytes();
+        assert!(bytes > 0);
+        let files = std::fs::metadata("./data/test1.kmc_pre")?.len()
+            + std::fs::metadata("./data/test1.kmc_suf")?.len();
+        assert!(bytes <= files);
+        assert_eq!(bytes, 622);
+        Ok(())
+    }
+
+    #[test]
+    fn test_info() -> Result<(), KmcError> {
+        let expected = KmcInfo {
+            k: 5,
+            mode: KmcMode::Occurrence,
+            counter_size: 1,
+            lut_prefix_length: 1,
+            signature_len: 0,
+            min_count: 2,
+            max_count: 1_000_000_000,
+            both_strands: true,
+            total_kmers: 291,
+        };
+        assert_eq!(KmcFile::open_ra("./data/test1")?.info(), expected);
+        assert_eq!(KmcFile::open_iter("./data/test1")?.info(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_both_strands() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert!(io.both_strands());
+        assert_eq!(io.count_kmer(&Kmer::from("TCTTA")?), 0);
+        assert_eq!(io.count_kmer(&Kmer::from("TCTTA")?.canonical()), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_total_kmers() -> Result<(), KmcError> {
+        assert_eq!(KmcFile::open_ra("./data/test1")?.total_kmers(), 291);
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        assert_eq!(io.total_kmers(), io.iter_u64().count() as u64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_kmer() -> Result<(), KmcError> {
+        let kmer = Kmer::from("TAAGA")?;
+        let s = kmer.to_string();
+        assert_eq!(&s, "TAAGA", "got {}", &s);
+        Ok(())
+    }
+
+    #[test]
+    fn test_kmer_errors() {
+        assert!(Kmer::from("TCN").is_err());
+        assert!(Kmer::from("actG").is_ok());
+    }
+
+    #[test]
+    fn test_k() -> Result<(), KmcError> {
+        for seq in &["", "A", "TAAGA", &"ACGT".repeat(10)] {
+            let kmer = Kmer::from(seq)?;
+            assert_eq!(kmer.k(), kmer.len() as usize);
+            assert_eq!(kmer.k(), seq.len());
+            assert_eq!(kmer.is_empty(), seq.is_empty());
+        }
+        assert!(Kmer::with_k(0).is_empty());
+        assert_eq!(Kmer::with_k(7).k(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_k() -> Result<(), KmcError> {
+        let dir = TempDir::new("large-k")?;
+        let seq = "ACCGTTTGACAGTAGCATTACGATCGACGTTAGGCA".repeat(8);
+        for &k in &[100, 200, MAX_K] {
+            let kmer = Kmer::with_len(k);
+            assert_eq!(kmer.len(), k);
+            assert_eq!(kmer.to_string(), "A".repeat(k as usize));
+
+            let mut kmers: Vec<Kmer> = (0..5)
+                .map(|i| Kmer::from(&seq[i..i + k as usize]).map(|kmer| kmer.canonical()))
+                .collect::<Result<_, _>>()?;
+            kmers.sort();
+            for kmer in &kmers {
+                assert_eq!(kmer.len(), k);
+                assert_eq!(Kmer::from(&kmer.to_string())?, *kmer);
+                assert_eq!(Kmer::from_bytes(k, &kmer.as_bytes())?, *kmer);
+                assert_eq!(kmer.reverse_complement().reverse_complement(), *kmer);
+            }
+
+            let path = dir.join(format!("k{}", k));
+            let mut writer = KmcWriter::create(&path, k)?;
+            for (count, kmer) in (1..).zip(&kmers) {
+                writer.add(kmer, count)?;
+            }
+            writer.finish()?;
+            let db = KmcFile::open_ra(&path)?;
+            assert_eq!(db.kmer_length(), k);
+            for (count, kmer) in (1..).zip(&kmers) {
+                assert_eq!(db.count_kmer(kmer), count);
+                assert_eq!(db.count_kmer(&kmer.reverse_complement().canonical()), count);
+            }
+            assert_eq!(db.count_kmer(&Kmer::with_len(k)), 0);
+            let mut db = KmcFile::open_iter(&path)?;
+            let listed: Vec<Kmer> = db.iter_kmers().map(|(kmer, _)| kmer).collect();
+            assert_eq!(listed, kmers);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_kmer() -> Result<(), KmcError> {
+        let empty = Kmer::with_k(0);
+        assert_eq!(empty.as_u64(), 0);
+        assert_eq!(empty.try_as_u64()?, 0);
+        assert_eq!(empty.to_string(), "");
+        assert!(empty.handle.data().is_empty());
+        assert_eq!(Kmer::from("")?, empty);
+        assert_eq!(empty.reverse_complement(), empty);
+
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.count_kmer(&empty), 0);
+        assert!(io.try_count_kmer(&empty).is_err());
+        assert!(!io.count_kmers(&[empty]).iter().any(|&c| c > 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lowercase() -> Result<(), KmcError> {
+        let kmer = Kmer::from("ACGT")?;
+        assert_eq!(kmer.to_string_lowercase(), "acgt");
+        assert_eq!(kmer.to_string(), "ACGT");
+        assert_eq!(Kmer::from("acgt")?.format_with(Case::Upper), "ACGT");
+        let long = "ACGTTGCA".repeat(9);
+        assert_eq!(
+            Kmer::from(&long)?.to_string_lowercase(),
+            long.to_lowercase()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_kmer_whitespace() -> Result<(), KmcError> {
+        assert_eq!(Kmer::from(" ACGT\n")?, Kmer::from("ACGT")?);
+        assert_eq!(Kmer::from("\tacgt\r\n")?.len(), 4);
+        assert!(Kmer::from("AC GT").is_err());
+        assert!(Kmer::from("AC\nGT").is_err());
+        assert!(Kmer::from(" \n")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_kmer_from_ascii() -> Result<(), KmcError> {
+        assert!(Kmer::from_ascii(b"TCN").is_err());
+        assert!(Kmer::from_ascii(b"T C").is_err());
+        assert_eq!(Kmer::from_ascii(b"actG")?.to_string(), "ACTG");
+        assert!(Kmer::from_ascii(b"TAAGA")? == Kmer::from("TAAGA")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_kmer() -> Result<(), KmcError> {
+        let mut kmer = Kmer::from("TAAGA")?;
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.count_kmer(&mut kmer), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_both_strands() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert!(io.both_strands());
+        // only the canonical TAAGA is stored
+        assert_eq!(io.count_both_strands(&Kmer::from("TAAGA")?), (4, 0));
+        assert_eq!(io.count_both_strands(&Kmer::from("TCTTA")?), (0, 4));
+        assert_eq!(io.count_both_strands(&Kmer::from("AAAAG")?), (0, 0));
+
+        let dir = TempDir::new("strands")?;
+        let mut writer = KmcWriter::create(dir.join("single"), 5)?.single_strand();
+        writer.add(&Kmer::from("TAAGA")?, 3)?;
+        writer.add(&Kmer::from("TCTTA")?, 1)?;
+        writer.finish()?;
+        let single = KmcFile::open_ra(dir.join("single"))?;
+        assert_eq!(single.count_both_strands(&Kmer::from("TAAGA")?), (3, 1));
+        assert_eq!(single.count_both_strands(&Kmer::from("TCTTA")?), (1, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_kmer_thresholded() -> Result<(), KmcError> {
+        let kmer = Kmer::from("TAAGA")?;
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.count_kmer_thresholded(&kmer, 0), 4);
+        assert_eq!(io.count_kmer_thresholded(&kmer, 3), 4);
+        assert_eq!(io.count_kmer_thresholded(&kmer, 4), 4);
+        assert_eq!(io.count_kmer_thresholded(&kmer, 5), 0);
+        assert_eq!(io.count_kmer_thresholded(&Kmer::from("TCTTA")?, 0), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_kmers() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        let kmers = vec![
+            Kmer::from("TAAGA")?,
+            Kmer::from("TAAG")?,
+            Kmer::from("AAAAA")?,
+            Kmer::from("TCTTA")?,
+        ];
+        assert_eq!(io.count_kmers(&kmers), vec![4, 0, 2, 0]);
+        assert!(io.count_kmers(&[]).is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_count_kmers() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        let kmers: Vec<Kmer> = (0..10_000).map(|i| Kmer::from_u64(5, i % 1024)).collect();
+        assert!(io.par_count_kmers(&kmers) == io.count_kmers(&kmers));
+        Ok(())
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send<T: Send>() {}
+        fn assert_sync<T: Sync>() {}
+        assert_send::<KmcFileRa>();
+        assert_sync::<KmcFileRa>();
+        assert_send::<KmcFileIter>();
+        assert_send::<Kmer>();
+        assert_sync::<Kmer>();
+    }
+
+    #[test]
+    fn test_shared_threads() -> Result<(), KmcError> {
+        let io = std::sync::Arc::new(KmcFile::open_ra("./data/test1")?);
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let io = io.clone();
+                std::thread::spawn(move || io.count_str("TAAGA"))
+            })
+            .collect();
+        for worker in workers {
+            assert_eq!(worker.join().unwrap()?, 4);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_into() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        let mut scratch = Kmer::with_k(0);
+        for i in 0..1024 {
+            let seq = Kmer::from_u64(5, i).to_string();
+            assert_eq!(io.count_into(&mut scratch, &seq)?, io.count_str(&seq)?);
+            assert_eq!(scratch.to_string(), seq);
+        }
+        assert_eq!(io.count_into(&mut scratch, "taaga\n")?, 4);
+        assert!(matches!(
+            io.count_into(&mut scratch, "TAAG"),
+            Err(KmcError::LengthMismatch {
+                expected: 5,
+                got: 4
+            })
+        ));
+        assert!(matches!(
+            io.count_into(&mut scratch, "TANGA"),
+            Err(KmcError::InvalidKmer(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_accepts() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert!(io.accepts(&Kmer::from("TAAGA")?));
+        assert!(io.accepts(&Kmer::with_k(5)));
+        assert!(!io.accepts(&Kmer::from("TAAGAC")?));
+        assert!(!io.accepts(&Kmer::with_k(0)));
+        let io = io.reopen_as_iter()?;
+        assert!(io.accepts(&io.new_kmer_buffer()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_str() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.count_str("TAAGA")?, 4);
+        assert!(matches!(
+            io.count_str("TAAG"),
+            Err(KmcError::LengthMismatch {
+                expected: 5,
+                got: 4
+            })
+        ));
+        assert!(matches!(
+            io.count_str("TAANA"),
+            Err(KmcError::InvalidKmer(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_counts_for_read() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(io.counts_for_read("TCTTAAGACG")?, vec![4, 2, 2, 4, 2, 0]);
+        assert_eq!(
+            io.counts_for_read("TCTTANGACGAC")?,
+            vec![4, 0, 0, 0, 0, 0, 0, 3]
+        );
+        assert!(io.counts_for_read("TCTT")?.is_empty());
+        for read in &["TCTTAAGACG", "TCTTANGACGAC", "tcttaagacg", "TCT"] {
+            assert_eq!(
+                io.counts_for_read_bytes(read.as_bytes())?,
+                io.counts_for_read(read)?
+            );
+        }
+        assert_eq!(io.counts_for_read_bytes(b"\xffTCTTA")?, vec![0, 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_u64_tcaaccttggaa() {
+        assert_eq!("TCAACCTTGGAA".len(), 12);
+        let kmer = Kmer::from_u64(12, 0b1101_0000_0101_1111_1010_0000);
+        assert_eq!(kmer.to_string(), "TCAACCTTGGAA");
+    }
+
+    #[test]
+    fn test_from_u64_ttttttttttttttttttttttttttttttc() {
+        assert_eq!("TTTTTTTTTTTTTTTTTTTTTTTTTTTTTTC".len(), 31);
+        assert_eq!(
+            "TTTTTTTTTTTTTTTTTTTTTTTTTTTTTTC",
+            Kmer::from_u64(
+                31,
+                0b1111111111111111111111111111111111111111111111111111111111111_01,
+            )
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_reverse_complement() -> Result<(), KmcError> {
+        let kmer = Kmer::from("TAAGA")?;
+        assert_eq!(kmer.reverse_complement().to_string(), "TCTTA");
+        assert_eq!(
+            kmer.reverse_complement().reverse_complement().to_string(),
+            "TAAGA"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reverse_complement_long() -> Result<(), KmcError> {
+        let seq = "ACGTTGCAAGGCTTAACCGGTTAACGTAGCTAGCATCGATCGAT";
+        let kmer = Kmer::from(seq)?;
+        let rc: String = seq
+            .chars()
+            .rev()
+            .map(|c| match c {
+                'A' => 'T',
+                'C' => 'G',
+                'G' => 'C',
+                _ => 'A',
+            })
+            .collect();
+        assert_eq!(kmer.reverse_complement().to_string(), rc);
+        Ok(())
+    }
+
+    #[test]
+    fn test_complement() -> Result<(), KmcError> {
+        let kmer = Kmer::from("ACGT")?;
+        assert_eq!(kmer.complement().to_string(), "TGCA");
+        let kmer = Kmer::from("TAAGA")?;
+        assert_eq!(kmer.complement().to_string(), "ATTCT");
+        assert_eq!(
+            kmer.complement()
+                .to_string()
+                .chars()
+                .rev()
+                .collect::<String>(),
+            kmer.reverse_complement().to_string()
+        );
+        assert!(kmer.complement().complement() == kmer);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reverse() -> Result<(), KmcError> {
+        let kmer = Kmer::from("TAAGA")?;
+        assert_eq!(kmer.reverse().to_string(), "AGAAT");
+        assert!(kmer.reverse().reverse() == kmer);
+        assert!(kmer.complement().reverse() == kmer.reverse_complement());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bases_chars() -> Result<(), KmcError> {
+        assert_eq!(Kmer::from("ACGT")?.chars().collect::<String>(), "ACGT");
+        assert_eq!(
+            Kmer::from("ACGT")?.bases().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+        let long = "ACCGTTTGACAGTAGCATTACGATCGACGTTAGGCA";
+        let kmer = Kmer::from(long)?;
+        assert_eq!(kmer.chars().collect::<String>(), long);
+        assert_eq!(kmer.bases().count(), 36);
+        assert_eq!(Kmer::with_k(0).chars().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_base_at() -> Result<(), KmcError> {
+        let kmer = Kmer::from("ACGT")?;
+        assert_eq!(
+            (0..5).map(|i| kmer.base_at(i)).collect::<Vec<_>>(),
+            vec![Some(0), Some(1), Some(2), Some(3), None]
+        );
+        assert_eq!(
+            (0..5).map(|i| kmer.base_char_at(i)).collect::<Vec<_>>(),
+            vec![Some('A'), Some('C'), Some('G'), Some('T'), None]
+        );
+        let long = Kmer::from(&"ACGT".repeat(10))?;
+        assert_eq!(long.base_char_at(38), Some('G'));
+        assert_eq!(long.base_char_at(40), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_roll() -> Result<(), KmcError> {
+        let mut kmer = Kmer::from("TTT")?;
+        let mut rolled = Vec::new();
+        for base in 0..4 {
+            kmer.roll(base);
+            rolled.push(kmer.to_string());
+        }
+        assert_eq!(rolled, vec!["TTA", "TAC", "ACG", "CGT"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_roll_long() -> Result<(), KmcError> {
+        let read = "ACGTTGCAAGGCTTAACCGGTTAACGTAGCTAGCATCGATCGATTTGCAGTAC";
+        let k = 33;
+        let mut kmer = Kmer::from(&read[..k])?;
+        for i in k..read.len() {
+            kmer.roll(Kmer::from(&read[i..=i])?.as_u64() as u8);
+            assert_eq!(kmer.to_string(), &read[i + 1 - k..=i]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_gc() -> Result<(), KmcError> {
+        assert_eq!(Kmer::from("GCGC")?.gc_count(), 4);
+        assert_eq!(Kmer::from("GCGC")?.gc_fraction(), 1.0);
+        assert_eq!(Kmer::from("TAAGA")?.gc_count(), 1);
+        assert_eq!(Kmer::from("TAAGA")?.gc_fraction(), 0.2);
+        assert_eq!(Kmer::from(&"ACGTA".repeat(10))?.gc_count(), 20);
+        assert_eq!(Kmer::with_k(0).gc_fraction(), 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hamming_distance() -> Result<(), KmcError> {
+        let acgt = Kmer::from("ACGT")?;
+        assert_eq!(acgt.hamming_distance(&Kmer::from("ACGA")?), Some(1));
+        assert_eq!(acgt.hamming_distance(&acgt), Some(0));
+        assert_eq!(acgt.hamming_distance(&Kmer::from("TGCA")?), Some(4));
+        assert_eq!(acgt.hamming_distance(&Kmer::from("ACGTA")?), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_neighbors_hamming1() -> Result<(), KmcError> {
+        let kmer = Kmer::from("ACG")?;
+        let neighbors = kmer.neighbors_hamming1();
+        assert_eq!(neighbors.len(), 9);
+        assert!(neighbors.iter().all(|n| *n != kmer));
+        assert!(neighbors
+            .iter()
+            .all(|n| n.hamming_distance(&kmer) == Some(1)));
+        let distinct: std::collections::HashSet<_> = neighbors.iter().collect();
+        assert_eq!(distinct.len(), 9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_minimizer() -> Result<(), KmcError> {
+        let kmer = Kmer::from("TGACGTAC")?;
+        let (pos, minimizer) = kmer.minimizer(4).unwrap();
+        assert_eq!(pos, 2);
+        assert_eq!(minimizer.to_string(), "ACGT");
+        assert!(kmer.minimizer(9).is_none());
+        assert!(kmer.minimizer(0).is_none());
+        assert_eq!(kmer.minimizer(8).unwrap().1.to_string(), "TGACGTAC");
+
+        let kmer = Kmer::from("TTTTGGGG")?;
+        let (pos, minimizer) = kmer.minimizer(3).unwrap();
+        assert_eq!((pos, minimizer.to_string().as_str()), (4, "GGG"));
+        let (pos, minimizer) = kmer.canonical_minimizer(3).unwrap();
+        assert_eq!((pos, minimizer.to_string().as_str()), (0, "AAA"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_slice() -> Result<(), KmcError> {
+        let kmer = Kmer::from("ACGTACGT")?;
+        assert_eq!(kmer.slice(0, 3).unwrap().to_string(), "ACG");
+        assert_eq!(kmer.slice(5, 3).unwrap().to_string(), "CGT");
+        assert_eq!(kmer.slice(2, 0).unwrap().len(), 0);
+        assert!(kmer.slice(0, 8).unwrap() == kmer);
+        assert!(kmer.slice(6, 3).is_none());
+        assert!(kmer.slice(9, 0).is_none());
+        assert!(kmer.slice(1, u32::MAX).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonical() -> Result<(), KmcError> {
+        let taaga = Kmer::from("TAAGA")?;
+        assert!(taaga.is_canonical());
+        assert_eq!(taaga.canonical().to_string(), "TAAGA");
+
+        let tctta = Kmer::from("TCTTA")?;
+        assert!(!tctta.is_canonical());
+        assert_eq!(tctta.canonical().to_string(), "TAAGA");
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonical_u64() -> Result<(), KmcError> {
+        for s in [
+            "TAAGA",
+            "TCTTA",
+            "ACGT",
+            "GGGGG",
+            "TTTTTTTTTTTTTTTTTTTTTTTTTTTTTTTG",
+        ]
+        .iter()
+        {
+            let kmer = Kmer::from(s)?;
+            assert_eq!(kmer.canonical_u64(), kmer.canonical().as_u64(), "{}", s);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonical_palindrome() -> Result<(), KmcError> {
+        // an odd k never equals its reverse complement, so take k = 4
+        let acgt = Kmer::from("ACGT")?;
+        assert_eq!(acgt.reverse_complement().to_string(), "ACGT");
+        assert!(acgt.is_canonical());
+        assert_eq!(acgt.canonical().to_string(), "ACGT");
+        Ok(())
+    }
+
+    #[test]
+    fn test_clone() -> Result<(), KmcError> {
+        let mut kmer = Kmer::from("TAAGA")?;
+        let copy = kmer.clone();
+        kmer.roll(0b01);
+        assert_eq!(kmer.to_string(), "AAGAC");
+        assert_eq!(copy.to_string(), "TAAGA");
+        kmer.set_u64(0);
+        assert_eq!(copy.to_string(), "TAAGA");
+        assert_eq!(copy.len(), 5);
+
+        let long = Kmer::from(&"ACGTTGCA".repeat(9))?;
+        assert!(long.clone() == long);
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug() -> Result<(), KmcError> {
+        let kmer = Kmer::from("TAAGA")?;
+        assert_eq!(format!("{:?}", kmer), "Kmer(\"TAAGA\", k=5)");
+        assert!(format!("{:?}", Some(kmer)).contains("TAAGA"));
+        assert_eq!(
+            format!("{:?}", KmcFile::open_ra("./data/test1")?),
+            "KmcFile { k: 5, mode: RandomAccess }"
+        );
+        assert_eq!(
+            format!("{:?}", KmcFile::open_iter("./data/test1")?),
+            "KmcFile { k: 5, mode: Listing }"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_str() -> Result<(), KmcError> {
+        assert_eq!("TAAGA".parse::<Kmer>()?, Kmer::from("TAAGA")?);
+        assert!(matches!(
+            "TAXGA".parse::<Kmer>(),
+            Err(KmcError::InvalidKmer(_))
+        ));
+        assert!(Kmer::from("TAXGA").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_from() -> Result<(), KmcError> {
+        use std::convert::TryFrom;
+
+        let kmer = Kmer::try_from(&b"TAAGA"[..])?;
+        assert_eq!(kmer, Kmer::from("TAAGA")?);
+        assert!(Kmer::try_from(&b"TANGA"[..]).is_err());
+
+        let val = kmer.as_u64();
+        assert_eq!(Kmer::try_from((5, val))?, kmer);
+        assert!(Kmer::try_from((4, val)).is_err());
+        assert!(Kmer::try_from((33, 0)).is_err());
+        assert_eq!(Kmer::try_from((32, u64::MAX))?.to_string(), "T".repeat(32));
+        Ok(())
+    }
+
+    #[test]
+    fn test_eq() -> Result<(), KmcError> {
+        assert!(Kmer::from("ACGT")? == Kmer::from_u64(4, 0b00_01_10_11));
+        assert!(Kmer::from("ACGT")? != Kmer::from("ACGA")?);
+        assert!(Kmer::from("ACGT")? != Kmer::from("ACGTA")?);
+        assert!(Kmer::from("AAAA")? != Kmer::from("AAAAA")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash() -> Result<(), KmcError> {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |kmer: &Kmer| {
+            let mut hasher = DefaultHasher::new();
+            kmer.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(
+            hash(&Kmer::from("TAAGA")?),
+            hash(&Kmer::from_u64(5, 0b11_00_00_10_00))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_set() -> Result<(), KmcError> {
+        let mut db = KmcFile::open_iter("./data/test1")?;
+        let k = db.kmer_length() as u8;
+        let set: std::collections::HashSet<Kmer> =
+            db.iter_u64().map(|(b, _)| Kmer::from_u64(k, b)).collect();
+        assert_eq!(set.len(), db.num_kmers());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ord() -> Result<(), KmcError> {
+        let mut seqs = vec![
+            "TAAGA", "ACGTA", "GATTA", "CCCCC", "ACGTC", "TTTTT", "AAAAA",
+        ];
+        let mut kmers = seqs
+            .iter()
+            .map(|s| Kmer::from(s))
+            .collect::<Result<Vec<_>, _>>()?;
+        seqs.sort_unstable();
+        kmers.sort();
+        assert_eq!(
+            kmers.iter().map(|k| k.to_string()).collect::<Vec<_>>(),
+            seqs
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ord_long() -> Result<(), KmcError> {
+        let a = Kmer::from("ACGTACGTACGTACGTACGTACGTACGTACGTAAAA")?;
+        let b = Kmer::from("ACGTACGTACGTACGTACGTACGTACGTACGTAAAC")?;
+        assert!(a < b);
+        assert_eq!(a.as_u64(), b.as_u64());
+        assert!(Kmer::from("ACG")? < Kmer::from("ACGA")?);
+        assert!(Kmer::from("ACGA")? < Kmer::from("ACT")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_as_u64_long() -> Result<(), KmcError> {
+        let seq = "TCAACCTTGGAATCAACCTTGGAATCAACCTTGGAAGTCA";
+        let kmer = Kmer::from(seq)?;
+        assert!(matches!(kmer.try_as_u64(), Err(KmcError::InvalidKmer(_))));
+        assert_eq!(kmer.as_u64(), Kmer::from(&seq[..32])?.as_u64());
+        assert_eq!(
+            Kmer::from(&seq[..33])?.as_u64(),
+            Kmer::from(&seq[..32])?.as_u64()
+        );
+        let kmer = Kmer::from(&seq[..32])?;
+        assert_eq!(kmer.try_as_u64()?, kmer.as_u64());
+        assert_eq!(Kmer::from("")?.try_as_u64()?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_from_u64() -> Result<(), KmcError> {
+        assert_eq!(
+            Kmer::try_from_u64(5, 0b11_00_00_10_00)?,
+            Kmer::from("TAAGA")?
+        );
+        assert!(matches!(
+            Kmer::try_from_u64(33, 0),
+            Err(KmcError::InvalidKmer(_))
+        ));
+        assert!(matches!(
+            Kmer::try_from_u64(4, 1 << 8),
+            Err(KmcError::InvalidKmer(_))
+        ));
+        assert_eq!(Kmer::try_from_u64(4, (1 << 8) - 1)?.to_string(), "TTTT");
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_set_u64() -> Result<(), KmcError> {
+        let mut kmer = Kmer::from("TAAGA")?;
+        assert!(matches!(
+            kmer.try_set_u64(1 << 10),
+            Err(KmcError::InvalidKmer(_))
+        ));
+        assert_eq!(kmer.to_string(), "TAAGA");
+        kmer.try_set_u64(0b11_11_11_11_11)?;
+        assert_eq!(kmer.to_string(), "TTTTT");
+
+        let mut long = Kmer::from(&"ACGT".repeat(10))?;
+        assert!(matches!(long.try_set_u64(0), Err(KmcError::InvalidKmer(_))));
+        let mut full = Kmer::with_k(32);
+        full.try_set_u64(u64::MAX)?;
+        assert_eq!(full.to_string(), "T".repeat(32));
+        let mut empty = Kmer::with_k(0);
+        empty.try_set_u64(0)?;
+        assert!(empty.try_set_u64(1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefix_suffix() -> Result<(), KmcError> {
+        let kmer = Kmer::from("ACGT")?;
+        assert_eq!(kmer.prefix(), Kmer::from("ACG")?);
+        assert_eq!(kmer.suffix(), Kmer::from("CGT")?);
+        assert!(Kmer::from("A")?.prefix().is_empty());
+        assert!(Kmer::from("A")?.suffix().is_empty());
+        assert!(Kmer::from("")?.suffix().is_empty());
+        let long = "TCAACCTTGGAATCAACCTTGGAATCAACCTTGGAAGTCA";
+        let kmer = Kmer::from(long)?;
+        assert_eq!(kmer.prefix().to_string(), long[..39]);
+        assert_eq!(kmer.suffix().to_string(), long[1..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_prepend() -> Result<(), KmcError> {
+        let kmer = Kmer::from("ACG")?;
+        assert_eq!(kmer.append_base(0b11), Kmer::from("ACGT")?);
+        assert_eq!(kmer.prepend_base(0b00), Kmer::from("AACG")?);
+        assert_eq!(kmer.to_string(), "ACG");
+        assert_eq!(Kmer::from("")?.append_base(0b10).to_string(), "G");
+        let long = Kmer::from(&"ACGT".repeat(8))?;
+        assert_eq!(long.append_base(0b01).to_string(), "ACGT".repeat(8) + "C");
+        assert_eq!(long.append_base(0b01).prefix(), long);
+        assert_eq!(long.prepend_base(0b10).suffix(), long);
+        Ok(())
+    }
+
+    #[test]
+    fn test_u128_40mer() -> Result<(), KmcError> {
+        let seq = "TCAACCTTGGAATCAACCTTGGAATCAACCTTGGAAGTCA";
+        assert_eq!(seq.len(), 40);
+        let val = Kmer::from(seq)?.as_u128();
+        let kmer = Kmer::from_u128(40, val);
+        assert_eq!(kmer.to_string(), seq);
+        assert_eq!(kmer.as_u128(), val);
+        Ok(())
+    }
+
+    #[test]
+    fn test_u128_64mer() {
+        let kmer = Kmer::from_u128(64, u128::MAX);
+        assert_eq!(kmer.to_string(), "T".repeat(64));
+        assert_eq!(kmer.as_u128(), u128::MAX);
+
+        let kmer = Kmer::from_u128(64, 0b01 << 126 | 0b10);
+        assert_eq!(kmer.to_string(), format!("C{}G", "A".repeat(62)));
+    }
+
+    #[test]
+    fn test_bytes_50mer() -> Result<(), KmcError> {
+        let seq = "ACGTTGCAAGGCTTAACCGGTTAACGTAGCTAGCATCGATCGATTTGCAG";
+        assert_eq!(seq.len(), 50);
+        let bytes = Kmer::from(seq)?.as_bytes();
+        assert_eq!(bytes.len(), 13);
+        assert_eq!(Kmer::from_bytes(50, &bytes)?.to_string(), seq);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes_length_mismatch() {
+        assert!(Kmer::from_bytes(5, &[0]).is_err());
+        assert!(Kmer::from_bytes(5, &[0, 0, 0]).is_err());
+        assert!(Kmer::from_bytes(8, &[0, 0]).is_ok());
+    }
+
+    #[test]
+    fn test_open_iter() -> Result<(), KmcError> {
+        let io = KmcFile::open_iter("./data/test1")?;
+        assert_eq!(io.kmer_length(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_count() -> Result<(), KmcError> {
+        assert_eq!(KmcFile::open_iter("./data/test1")?.iter_u64().count(), 291);
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_kmers() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let kmers: Vec<(Kmer, usize)> = io.iter_kmers().collect();
+        assert!(io.restart());
+        let items: Vec<(u64, usize)> = io.iter_u64().collect();
+        assert_eq!(kmers.len(), 291);
+        assert_eq!(kmers.len(), items.len());
+        assert_eq!(
+            kmers.iter().map(|(_, c)| c).sum::<usize>(),
+            items.iter().map(|(_, c)| c).sum::<usize>()
+        );
+        assert!(kmers
+            .iter()
+            .zip(items.iter())
+            .all(|((kmer, _), (b, _))| kmer.as_u64() == *b));
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_strings() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let strings: Vec<(String, usize)> = io.iter_strings().collect();
+        assert_eq!(strings.len(), 291);
+        assert!(strings.contains(&("TAAGA".to_string(), 4)));
+        assert!(strings.iter().all(|(s, _)| s.len() == 5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let hist = io.histogram(10);
+        assert_eq!(hist, vec![0, 0, 136, 85, 41, 19, 6, 3, 0, 1, 0]);
+        assert!(io.restart());
+        assert_eq!(hist.iter().sum::<u64>() as usize, io.num_kmers());
+        assert!(io.restart());
+        assert_eq!(io.histogram(0), vec![291]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_u64_with_progress() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        for &(every, calls) in &[(1, 291), (2, 145), (100, 2), (291, 1), (292, 0), (0, 0)] {
+            assert!(io.restart());
+            let mut reported = Vec::new();
+            let items: Vec<_> = io
+                .iter_u64_with_progress(every, |n| reported.push(n))
+                .collect();
+            assert_eq!(items.len(), 291);
+            assert_eq!(reported.len(), calls, "every = {}", every);
+            assert!(reported
+                .iter()
+                .enumerate()
+                .all(|(i, &n)| n == (i + 1) * every));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_u64_canonical() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        for (val, _, canonical) in io.iter_u64_canonical().take(20) {
+            assert_eq!(canonical, Kmer::from_u64(5, val).is_canonical());
+        }
+
+        let dir = TempDir::new("canonical")?;
+        let mut writer = KmcWriter::create(dir.join("single"), 5)?.single_strand();
+        for seq in &["AAAAC", "GTTTT", "TAAGA", "TCTTA"] {
+            writer.add(&Kmer::from(seq)?, 1)?;
+        }
+        writer.finish()?;
+        let mut io = KmcFile::open_iter(dir.join("single"))?;
+        let flags: Vec<bool> = io.iter_u64_canonical().map(|(_, _, c)| c).collect();
+        assert_eq!(flags, vec![true, false, true, false]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_num_kmers_keeps_position() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let mut kmer = Kmer::with_k(5);
+        assert_eq!(io.num_kmers(), 291);
+        assert_eq!(io.read_next(&mut kmer), Some(2));
+        assert_eq!(kmer.to_string(), "AAAAA");
+        assert_eq!(io.num_kmers(), 291);
+        assert_eq!(io.read_next(&mut kmer), Some(2));
+        assert_eq!(kmer.to_string(), "AAAAC");
+        Ok(())
+    }
+
+    #[test]
+    fn test_size_hint() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        assert_eq!(io.iter_u64().size_hint(), (291, Some(291)));
+        let mut iter = io.iter_u64();
+        iter.nth(90);
+        assert_eq!(iter.size_hint(), (200, Some(200)));
+        assert_eq!(iter.count(), 200);
+        assert_eq!(io.iter_u64().size_hint(), (0, Some(0)));
+        assert!(io.restart());
+        assert_eq!(io.iter_u64().size_hint(), (291, Some(291)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exact_size() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        assert_eq!(io.iter_u64().len(), 291);
+        let mut iter = io.iter_u64();
+        iter.next();
+        assert_eq!(iter.len(), 290);
+        assert_eq!(iter.len(), iter.count());
+        Ok(())
+    }
+
+    #[test]
+    fn test_equals() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        assert!(io.equals(&mut KmcFile::open_iter("./data/test1")?));
+
+        let dir = TempDir::new("equals")?;
+        assert!(io.restart());
+        let mut writer = KmcWriter::create(dir.join("changed"), 5)?;
+        for (kmer, count) in io.iter_kmers() {
+            let count = if kmer.to_string() == "TAAGA" {
+                5
+            } else {
+                count
+            };
+            writer.add(&kmer, count as u32)?;
+        }
+        writer.finish()?;
+        let mut writer = KmcWriter::create(dir.join("fewer"), 5)?;
+        writer.add(&Kmer::from("AAAAA")?, 2)?;
+        writer.finish()?;
+        let mut changed = KmcFile::open_iter(dir.join("changed"))?;
+        let mut fewer = KmcFile::open_iter(dir.join("fewer"))?;
+        assert!(io.restart());
+        assert!(!io.equals(&mut changed));
+        assert!(io.restart());
+        assert!(!io.equals(&mut fewer));
+        assert!(!fewer.equals(&mut io));
+
+        let mut writer = KmcWriter::create(dir.join("sorted"), 5)?;
+        writer.add(&Kmer::from("AAAAC")?, 2)?;
+        writer.add(&Kmer::from("TAAGA")?, 4)?;
+        writer.finish()?;
+        write_kmc2(&dir.join("kmc2"), 5, &[&[("TAAGA", 4)], &[("AAAAC", 2)]])?;
+        write_kmc2(&dir.join("other"), 5, &[&[("TAAGA", 5)], &[("AAAAC", 2)]])?;
+        let mut sorted = KmcFile::open_iter(dir.join("sorted"))?;
+        let mut kmc2 = KmcFile::open_iter(dir.join("kmc2"))?;
+        let mut other = KmcFile::open_iter(dir.join("other"))?;
+        assert!(sorted.equals(&mut kmc2));
+        assert!(sorted.restart() && kmc2.restart());
+        assert!(kmc2.equals(&mut sorted));
+        assert!(kmc2.restart());
+        assert!(!kmc2.equals(&mut other));
+        Ok(())
+    }
+
+    #[test]
+    fn test_head() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let head = io.head(3);
+        assert_eq!(head.len(), 3);
+        assert_eq!(head[2], (Kmer::from("AAAAT")?, 3));
+        assert_eq!(io.head(0).len(), 0);
+        assert_eq!(io.head(1000).len(), 288);
+        assert!(io.head(3).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_prefix() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let all: Vec<(Kmer, usize)> = io.iter_kmers().collect();
+        for seq in &["", "A", "TG", "CGA", "TAAGA", "TCTTA", "TAAGAC"] {
+            assert!(io.restart());
+            let expected: Vec<&(Kmer, usize)> = all
+                .iter()
+                .filter(|(kmer, _)| kmer.to_string().starts_with(seq))
+                .collect();
+            let found: Vec<(Kmer, usize)> = io.iter_prefix(&Kmer::from(seq)?).collect();
+            assert_eq!(found.iter().collect::<Vec<_>>(), expected, "{}", seq);
+        }
+        assert!(io.restart());
+        assert_eq!(io.iter_prefix(&Kmer::from("TG")?).count(), 3);
+
+        let dir = TempDir::new("prefix-kmc2")?;
+        write_kmc2(
+            &dir.join("kmc2"),
+            5,
+            &[&[("CACAG", 3), ("TAAGA", 4)], &[("AAAAC", 2), ("CACAC", 1)]],
+        )?;
+        let mut kmc2 = KmcFile::open_iter(dir.join("kmc2"))?;
+        let found: Vec<(String, usize)> = kmc2
+            .iter_prefix(&Kmer::from("CA")?)
+            .map(|(kmer, count)| (kmer.to_string(), count))
+            .collect();
+        assert_eq!(found, [("CACAG".to_string(), 3), ("CACAC".to_string(), 1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_with() -> Result<(), KmcError> {
+        let mut a = KmcFile::open_iter("./data/test1")?;
+        let mut b = KmcFile::open_iter("./data/test1")?;
+        let merged: Vec<_> = a.merge_with(&mut b)?.collect();
+        assert_eq!(merged.len(), 291);
+        assert!(merged.iter().all(|(_, ca, cb)| ca.is_some() && ca == cb));
+        assert!(merged.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(a.restart());
+        let counts: Vec<_> = a.iter_kmers().map(|(_, count)| Some(count)).collect();
+        assert_eq!(
+            merged.into_iter().map(|(_, ca, _)| ca).collect::<Vec<_>>(),
+            counts
+        );
+
+        assert!(a.restart());
+        assert!(b.restart());
+        a.head(10);
+        let merged: Vec<_> = a.merge_with(&mut b)?.collect();
+        assert_eq!(merged.len(), 291);
+        assert_eq!(merged.iter().filter(|(_, ca, _)| ca.is_none()).count(), 10);
+        assert_eq!(merged[0].0.to_string(), "AAAAA");
+        assert_eq!((merged[0].1, merged[0].2), (None, Some(2)));
+
+        let dir = TempDir::new("merge-kmc2")?;
+        write_kmc2(
+            &dir.join("kmc2"),
+            5,
+            &[&[("TAAGA", 4)], &[("AAAAC", 2), ("CACAG", 3)]],
+        )?;
+        let mut kmc2 = KmcFile::open_iter(dir.join("kmc2"))?;
+        assert_eq!(kmc2.signature_len(), 5);
+        let listed: Vec<String> = kmc2
+            .iter_kmers()
+            .map(|(kmer, _)| kmer.to_string())
+            .collect();
+        assert_eq!(listed, ["TAAGA", "AAAAC", "CACAG"]);
+        assert!(kmc2.restart());
+        assert!(a.restart());
+        assert!(matches!(
+            a.merge_with(&mut kmc2),
+            Err(KmcError::Unsorted { .. })
+        ));
+        assert!(matches!(
+            kmc2.merge_with(&mut a),
+            Err(KmcError::Unsorted { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_where() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        assert_eq!(io.count_where(|_, count| count == 4), 41);
+        assert_eq!(io.count_where(|_, _| true), 0);
+        assert!(io.restart());
+        assert_eq!(io.count_where(|_, _| true), 291);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fold_entries() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let sum = io.fold_entries(0, |sum, _, count| sum + count);
+        assert!(io.restart());
+        let mut kmer = Kmer::with_k(5);
+        let mut expected = 0;
+        while let Some(count) = io.read_next(&mut kmer) {
+            expected += count;
+        }
+        assert_eq!(sum, expected);
+        assert_eq!(sum, 852);
+        assert!(io.restart());
+        let last = io.fold_entries(None, |_, kmer, _| Some(kmer.to_string()));
+        assert_eq!(last.as_deref(), Some("TTCAA"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_stats() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        io.head(10);
+        let stats = io.count_stats()?;
+        assert_eq!(stats.distinct, 291);
+        assert_eq!(stats.total, 852);
+        assert_eq!(stats.max, 9);
+        assert!((stats.mean - 852.0 / 291.0).abs() < 1e-12);
+        assert_eq!(io.head(1), vec![(Kmer::from("AAAAA")?, 2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_block() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let items: Vec<(u64, usize)> = io.iter_u64().collect();
+        assert!(io.restart());
+        let (mut kmers, mut counts) = (vec![0; 64], vec![0; 64]);
+        let mut blocks = Vec::new();
+        loop {
+            let n = io.read_block(&mut kmers, &mut counts)?;
+            if n == 0 {
+                break;
+            }
+            blocks.extend(kmers[..n].iter().copied().zip(counts[..n].iter().copied()));
+        }
+        assert_eq!(blocks.len(), 291);
+        assert_eq!(blocks, items);
+        assert!(io.restart());
+        assert_eq!(io.read_block(&mut kmers, &mut counts[..10])?, 10);
+        assert_eq!(io.iter_u64().len(), 281);
+
+        let dir = TempDir::new("read-block-k33")?;
+        let mut writer = KmcWriter::create(dir.join("k33"), 33)?;
+        writer.add(&Kmer::from(&"A".repeat(33))?, 1)?;
+        writer.add(&Kmer::from(&format!("{}C", "A".repeat(32)))?, 2)?;
+        writer.finish()?;
+        let mut io = KmcFile::open_iter(dir.join("k33"))?;
+        assert!(matches!(
+            io.read_block(&mut kmers, &mut counts),
+            Err(KmcError::InvalidKmer(_))
+        ));
+        assert_eq!(io.iter_kmers().count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let mut out = Vec::new();
+        io.dump(&mut out, false)?;
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().count(), 291);
+        assert!(text.lines().any(|line| line == "TAAGA\t4"));
+        assert!(io.restart());
+        let mut sorted = Vec::new();
+        io.dump(&mut sorted, true)?;
+        assert_eq!(String::from_utf8(sorted).unwrap(), text);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_read_next() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let mut kmer = Kmer::with_k(5);
+        let mut n = 0;
+        while io.try_read_next(&mut kmer)?.is_some() {
+            n += 1;
+        }
+        assert_eq!(n, 291);
+        assert!(io.try_read_next(&mut Kmer::with_k(4)).is_err());
+
+        let dir = TempDir::new("truncated")?;
+        std::fs::copy("./data/test1.kmc_pre", dir.join("test1.kmc_pre"))?;
+        // drop the last 100 records but keep the end marker
+        let suf = std::fs::read("./data/test1.kmc_suf")?;
+        let mut truncated = suf[..suf.len() - 4 - 100 * 2].to_vec();
+        truncated.extend_from_slice(b"KMCS");
+        std::fs::write(dir.join("test1.kmc_suf"), truncated)?;
+        let result = KmcFile::open_iter(dir.join("test1")).and_then(|mut io| {
+            let mut kmer = io.new_kmer_buffer();
+            io.try_read_next(&mut kmer)
+        });
+        assert!(matches!(result, Err(KmcError::Format(_))), "{:?}", result);
+        Ok(())
+    }
+
+    /// Write `AAAAC` (count 2) and `TAAGA` (count 4) with the counts in
+    /// `min_count..`, and return the bytes of both files.
+    fn write_two_kmers(dir: &TempDir, min_count: u32) -> Result<(Vec<u8>, Vec<u8>), KmcError> {
+        let mut writer = KmcWriter::create(dir.join("two"), 5)?.count_range(min_count, u32::MAX);
+        writer.add(&Kmer::from("AAAAC")?, 2)?;
+        writer.add(&Kmer::from("TAAGA")?, 4)?;
+        writer.finish()?;
+        Ok((
+            std::fs::read(dir.join("two.kmc_pre"))?,
+            std::fs::read(dir.join("two.kmc_suf"))?,
+        ))
+    }
+
+    #[test]
+    fn test_try_read_next_zero_counter() -> Result<(), KmcError> {
+        // with min_count 0, KMC lists a zero counter rather than skipping it
+        let dir = TempDir::new("zero-counter")?;
+        let (_, mut suf) = write_two_kmers(&dir, 0)?;
+        // records of one suffix byte and a counter of four bytes
+        suf[4 + 5 + 1..4 + 5 + 5].copy_from_slice(&[0; 4]);
+        std::fs::write(dir.join("two.kmc_suf"), suf)?;
+        let mut io = KmcFile::open_iter(dir.join("two"))?;
+        let mut kmer = io.new_kmer_buffer();
+        assert_eq!(io.try_read_next(&mut kmer)?, Some(2));
+        let result = io.try_read_next(&mut kmer);
+        match result {
+            Err(KmcError::Format(reason)) => {
+                assert!(reason.ends_with("zero counter"), "{}", reason)
+            }
+            _ => panic!("expected KmcError::Format, got {:?}", result),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_read_next_missing_records() -> Result<(), KmcError> {
+        // the header claims three more records; the file holds them, but
+        // their counters are zero, so KMC skips them (below min_count 1)
+        let dir = TempDir::new("missing-records")?;
+        let (mut pre, mut suf) = write_two_kmers(&dir, 1)?;
+        let total = pre.len() - 8 - 64 + 24;
+        pre[total..total + 8].copy_from_slice(&5u64.to_le_bytes());
+        suf.splice(suf.len() - 4..suf.len() - 4, vec![0; 3 * 5]);
+        std::fs::write(dir.join("two.kmc_pre"), pre)?;
+        std::fs::write(dir.join("two.kmc_suf"), suf)?;
+        let mut io = KmcFile::open_iter(dir.join("two"))?;
+        let mut kmer = io.new_kmer_buffer();
+        assert_eq!(io.try_read_next(&mut kmer)?, Some(2));
+        assert_eq!(io.try_read_next(&mut kmer)?, Some(4));
+        let result = io.try_read_next(&mut kmer);
+        match result {
+            Err(KmcError::Format(reason)) => {
+                assert!(reason.ends_with("ended after 2 of 5 kmers"), "{}", reason)
+            }
+            _ => panic!("expected KmcError::Format, got {:?}", result),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_restart() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let first: Vec<(u64, usize)> = io.iter_u64().collect();
+        assert!(io.restart());
+        let second: Vec<(u64, usize)> = io.iter_u64().collect();
+        assert_eq!(first.len(), 291);
+        assert_eq!(first, second);
+        io.head(17);
+        assert!(io.restart());
+        assert_eq!(io.iter_u64().collect::<Vec<_>>(), first);
+
+        // as after a failed restart
+        assert!(io.restart());
+        io.stalled = true;
+        assert_eq!(io.iter_u64().len(), 0);
+        assert_eq!(io.read_next(&mut Kmer::with_k(5)), None);
+        assert_eq!(io.read_block(&mut [0; 4], &mut [0; 4])?, 0);
+        assert!(io.restart());
+        assert_eq!(io.head(1).len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_kmer_buffer() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let mut kmer = io.new_kmer_buffer();
+        assert_eq!(kmer.len(), io.kmer_length());
+        let mut n = 0;
+        while io.read_next(&mut kmer).is_some() {
+            n += 1;
+        }
+        assert_eq!(n, 291);
+        assert_eq!(kmer.to_string(), "TTCAA");
+        Ok(())
+    }
+
+    #[test]
+    fn test_into_iter_u64() -> Result<(), KmcError> {
+        fn open(path: &str) -> Result<impl Iterator<Item = (u64, usize)> + 'static, KmcError> {
+            Ok(KmcFile::open_iter(path)?.into_iter_u64())
+        }
+
+        let owned: Vec<(u64, usize)> = open("./data/test1")?.collect();
+        let borrowed: Vec<(u64, usize)> = KmcFile::open_iter("./data/test1")?.iter_u64().collect();
+        assert_eq!(owned, borrowed);
+        let handle = std::thread::spawn(move || open("./data/test1").map(|it| it.count()));
+        assert_eq!(handle.join().unwrap()?, 291);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cursor() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let mut cursor = io.cursor();
+        assert_eq!(cursor.current_count(), 0);
+        assert!(cursor.advance());
+        assert!(cursor.advance());
+        assert_eq!(cursor.current_kmer().to_string(), "AAAAC");
+        assert_eq!(cursor.current_count(), 2);
+        assert_eq!(cursor.current_kmer().to_string(), "AAAAC");
+        assert!(cursor.advance());
+        assert_eq!(cursor.current_kmer().to_string(), "AAAAT");
+        assert_eq!(cursor.current_count(), 3);
+        let mut n = 3;
+        while cursor.advance() {
+            n += 1;
+        }
+        assert_eq!(n, 291);
+        assert_eq!(cursor.current_count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_filtered() -> Result<(), KmcError> {
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        assert_eq!(io.iter_filtered(4, usize::MAX).count(), 70);
+        assert!(io.restart());
+        assert_eq!(io.iter_filtered(4, 4).count(), 41);
+        assert!(io.restart());
+        assert!(io.iter_filtered(4, 4).all(|(_, c)| c == 4));
+        assert!(io.restart());
+        assert_eq!(io.iter_filtered(0, usize::MAX).count(), 291);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_hashmap() -> Result<(), KmcError> {
+        let map = KmcFile::open_iter("./data/test1")?.to_hashmap();
+        assert_eq!(map.len(), 291);
+        assert_eq!(map[&Kmer::from("TAAGA")?], 4);
+        assert_eq!(map.get(&Kmer::from("TCTTA")?), None);
         Ok(())
     }
 
     #[test]
-    fn test_iter_count_taaga() -> Result<(), String> {
+    fn test_iter_count_taaga() -> Result<(), KmcError> {
         assert_eq!(
             KmcFile::open_iter("./data/test1")?
                 .iter_u64()
                 .filter(|&(b, _)| { b == 0b11_00_00_10_00 })
                 .map(|(_, c)| c)
-                .next()
-                .ok_or("should not happen")?,
-            4
+                .next(),
+            Some(4)
         );
         Ok(())
     }
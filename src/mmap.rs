@@ -0,0 +1,214 @@
+//! Random access via memory mapping, see [KmcMmap].
+use crate::writer::split_kmer;
+use crate::{db_prefix, KmcError, Kmer};
+use memmap2::Mmap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// A data base opened for random access by memory mapping its `.kmc_suf` file
+/// instead of loading it like [KmcFile::open_ra](crate::KmcFile::open_ra).
+///
+/// KMC's API cannot map files, so the look up is implemented in Rust:
+/// the look up table of the `.kmc_pre` file is cached in memory and narrows a
+/// query down to a range of records in the mapped `.kmc_suf` file, which is
+/// then binary searched.
+///
+/// Memory trade-off: [KmcFile::open_ra](crate::KmcFile::open_ra) reads the
+/// whole `.kmc_suf` file into memory when opening, so every handle costs
+/// about [KmcFile::estimated_ram_bytes](crate::KmcFile::estimated_ram_bytes).
+/// Here, opening is cheap and the operating system loads the pages on demand
+/// and shares them with all other handles (and processes) mapping the same
+/// file.
+/// In turn, the first queries are slower (page faults), and if the file does
+/// not fit into memory, its pages might be read from disk repeatedly.
+///
+/// Only data bases in the format of KMC 1 counting occurrences are supported.
+/// ```rust
+/// let db = kmc_rs::KmcMmap::open("data/test1")?;
+/// assert_eq!(db.count_kmer(&kmc_rs::Kmer::from("TAAGA")?), 4);
+/// # Ok::<(), kmc_rs::KmcError>(())
+/// ```
+pub struct KmcMmap {
+    k: u32,
+    lut_len: u32,
+    counter_size: usize,
+    both_strands: bool,
+    /// Index of the first record of every prefix and the total number of records.
+    lut: Vec<u64>,
+    suffixes: Mmap,
+}
+
+impl KmcMmap {
+    /// Open the data base `path` (a suffix `.kmc_pre` or `.kmc_suf` is ignored).
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, KmcError> {
+        let prefix = db_prefix(path.as_ref())?;
+        let pre = map(PathBuf::from(format!("{}.kmc_pre", prefix)))?;
+        let malformed = |reason: &str| KmcError::Format(format!("{}.kmc_pre: {}", prefix, reason));
+        let n = pre.len();
+        if n < 12 || &pre[..4] != b"KMCP" || &pre[n - 4..] != b"KMCP" {
+            return Err(malformed("missing marker KMCP"));
+        }
+        let header_len = u32_at(&pre, n - 8) as usize;
+        if header_len < 64 || header_len + 12 > n {
+            return Err(malformed("invalid header size"));
+        }
+        if u32_at(&pre, n - 12) != 0 {
+            return Err(malformed("only the format of KMC 1 is supported"));
+        }
+        let header = &pre[n - 8 - header_len..n - 8];
+        let k = u32_at(header, 0);
+        if u32_at(header, 4) != 0 {
+            return Err(malformed("only counting occurrences is supported"));
+        }
+        let counter_size = u32_at(header, 8) as usize;
+        let lut_len = u32_at(header, 12);
+        let total = u64_at(header, 24);
+        let both_strands = header[32] == 0;
+        if k > crate::MAX_K || lut_len > k || (k - lut_len) % 4 != 0 || counter_size > 8 {
+            return Err(malformed("invalid header"));
+        }
+        let entries = 1usize
+            .checked_shl(2 * lut_len)
+            .filter(|&entries| entries <= (n - 12 - header_len) / 8)
+            .ok_or_else(|| malformed("look up table too short"))?;
+        let lut: Vec<u64> = (0..entries)
+            .map(|i| u64_at(&pre, 4 + 8 * i))
+            .chain(std::iter::once(total))
+            .collect();
+        if lut.windows(2).any(|w| w[0] > w[1]) {
+            return Err(malformed("look up table not sorted"));
+        }
+
+        let suf_path = PathBuf::from(format!("{}.kmc_suf", prefix));
+        let suffixes = map(suf_path.clone())?;
+        let record = (k - lut_len) as u64 / 4 + counter_size as u64;
+        if suffixes.len() as u64 != 8 + total * record
+            || &suffixes[..4] != b"KMCS"
+            || &suffixes[suffixes.len() - 4..] != b"KMCS"
+        {
+            return Err(KmcError::Format(format!(
+                "{} does not hold {} kmers",
+                suf_path.display(),
+                total
+            )));
+        }
+        Ok(Self {
+            k,
+            lut_len,
+            counter_size,
+            both_strands,
+            lut,
+            suffixes,
+        })
+    }
+
+    /// The parameter `k` of the data base.
+    pub fn kmer_length(&self) -> u32 {
+        self.k
+    }
+
+    /// Whether both strands were counted, see [KmcFile::both_strands](crate::KmcFile::both_strands).
+    pub fn both_strands(&self) -> bool {
+        self.both_strands
+    }
+
+    /// Number of kmers in the data base.
+    pub fn total_kmers(&self) -> u64 {
+        self.lut[self.lut.len() - 1]
+    }
+
+    /// Same as [KmcFile::count_kmer](crate::KmcFile::count_kmer).
+    pub fn count_kmer(&self, kmer: &Kmer) -> usize {
+        if kmer.len() != self.k {
+            return 0;
+        }
+        let (prefix, suffix) = split_kmer(kmer, self.lut_len);
+        let record = suffix.len() + self.counter_size;
+        let (mut lo, mut hi) = (self.lut[prefix] as usize, self.lut[prefix + 1] as usize);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let start = 4 + mid * record;
+            let entry = &self.suffixes[start..start + record];
+            match entry[..suffix.len()].cmp(&suffix) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => {
+                    return entry[suffix.len()..]
+                        .iter()
+                        .rev()
+                        .fold(0, |count, &b| (count << 8) | b as usize)
+                }
+            }
+        }
+        0
+    }
+}
+
+fn map(path: PathBuf) -> Result<Mmap, KmcError> {
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(source) => return Err(KmcError::MissingFile { path, source }),
+    };
+    // SAFETY: like KMC, we assume the data base is not modified while it is open.
+    Ok(unsafe { Mmap::map(&file) }?)
+}
+
+fn u32_at(bytes: &[u8], pos: usize) -> u32 {
+    let mut le = [0; 4];
+    le.copy_from_slice(&bytes[pos..pos + 4]);
+    u32::from_le_bytes(le)
+}
+
+fn u64_at(bytes: &[u8], pos: usize) -> u64 {
+    let mut le = [0; 8];
+    le.copy_from_slice(&bytes[pos..pos + 8]);
+    u64::from_le_bytes(le)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use crate::KmcFile;
+
+    #[test]
+    fn test_same_as_open_ra() -> Result<(), KmcError> {
+        let db = KmcMmap::open("./data/test1")?;
+        let io = KmcFile::open_ra("./data/test1")?;
+        assert_eq!(db.kmer_length(), 5);
+        assert!(db.both_strands());
+        assert_eq!(db.total_kmers(), 291);
+        for i in 0..1024 {
+            let kmer = Kmer::from_u64(5, i);
+            assert_eq!(db.count_kmer(&kmer), io.count_kmer(&kmer), "{}", kmer);
+        }
+        assert_eq!(db.count_kmer(&Kmer::from("TAAGA")?), 4);
+        assert_eq!(db.count_kmer(&Kmer::from("TAAG")?), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing() {
+        assert!(matches!(
+            KmcMmap::open("./data/missing"),
+            Err(KmcError::MissingFile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_malformed_header() -> Result<(), KmcError> {
+        let dir = TempDir::new("mmap-header")?;
+        std::fs::copy("./data/test1.kmc_suf", dir.join("test1.kmc_suf"))?;
+        let pre = std::fs::read("./data/test1.kmc_pre")?;
+        let header = pre.len() - 8 - 64;
+        for &(k, lut_len) in &[(6, 1), (37, 33), (104, 100), (1000, 4)] {
+            let mut pre = pre.clone();
+            pre[header..header + 4].copy_from_slice(&u32::to_le_bytes(k));
+            pre[header + 12..header + 16].copy_from_slice(&u32::to_le_bytes(lut_len));
+            std::fs::write(dir.join("test1.kmc_pre"), pre)?;
+            let result = KmcMmap::open(dir.join("test1"));
+            assert!(matches!(result, Err(KmcError::Format(_))), "k = {}", k);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+//! Marker types for the modes a [KmcFile](crate::KmcFile) can be opened in.
+//!
+//! Methods that only work in one mode are only available in that mode, e.g.
+//! a data base opened for listing cannot be queried:
+//! ```compile_fail
+//! let db = kmc_rs::KmcFile::open_iter("data/test1")?;
+//! db.count_kmer(&kmc_rs::Kmer::from("TAAGA")?);
+//! # Ok::<(), kmc_rs::KmcError>(())
+//! ```
+use crate::OpenMode;
+
+/// Random access mode, see [KmcFile::open_ra](crate::KmcFile::open_ra).
+pub enum RandomAccess {}
+
+/// Listing mode, see [KmcFile::open_iter](crate::KmcFile::open_iter).
+pub enum Listing {}
+
+/// Implemented by [RandomAccess] and [Listing], only.
+pub trait Mode: private::Sealed {
+    /// The corresponding value known at run time.
+    const MODE: OpenMode;
+}
+
+impl Mode for RandomAccess {
+    const MODE: OpenMode = OpenMode::RandomAccess;
+}
+
+impl Mode for Listing {
+    const MODE: OpenMode = OpenMode::Listing;
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::RandomAccess {}
+    impl Sealed for super::Listing {}
+}
@@ -0,0 +1,124 @@
+//! Configure how to open a data base, see [KmcOpenOptions].
+use crate::mode::{self, Mode};
+use crate::{KmcError, KmcFile};
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Options for opening a [KmcFile]; the mode is part of the type, as for [KmcFile].
+///
+/// By default, a data base is opened in random access mode and it is checked
+/// that both of its files can be read before KMC is asked to open them.
+/// ```rust
+/// use kmc_rs::KmcOpenOptions;
+///
+/// let db = KmcOpenOptions::new().open("data/test1")?;
+/// assert_eq!(db.count_str("TAAGA")?, 4);
+/// let mut db = KmcOpenOptions::new()
+///     .listing()
+///     .validate_exists(false)
+///     .open("data/test1")?;
+/// assert_eq!(db.iter_kmers().count(), 291);
+/// # Ok::<(), kmc_rs::KmcError>(())
+/// ```
+pub struct KmcOpenOptions<M = mode::RandomAccess> {
+    validate_exists: bool,
+    mode: PhantomData<M>,
+}
+
+impl KmcOpenOptions {
+    /// Default options, see [KmcOpenOptions].
+    pub fn new() -> Self {
+        Self {
+            validate_exists: true,
+            mode: PhantomData,
+        }
+    }
+}
+
+impl Default for KmcOpenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Mode> KmcOpenOptions<M> {
+    fn with_mode<N: Mode>(self) -> KmcOpenOptions<N> {
+        KmcOpenOptions {
+            validate_exists: self.validate_exists,
+            mode: PhantomData,
+        }
+    }
+
+    /// Open in random access mode (see [KmcFile::open_ra]).
+    pub fn random_access(self) -> KmcOpenOptions<mode::RandomAccess> {
+        self.with_mode()
+    }
+
+    /// Open in listing mode (see [KmcFile::open_iter]).
+    pub fn listing(self) -> KmcOpenOptions<mode::Listing> {
+        self.with_mode()
+    }
+
+    /// Whether to check that the `.kmc_pre` and `.kmc_suf` files can be read,
+    /// in order to report a [KmcError::MissingFile] rather than a
+    /// [KmcError::OpenFailed].
+    pub fn validate_exists(mut self, validate: bool) -> Self {
+        self.validate_exists = validate;
+        self
+    }
+
+    /// Open the data base `path`; a suffix `.kmc_pre` or `.kmc_suf` is ignored.
+    pub fn open(&self, path: impl AsRef<Path>) -> Result<KmcFile<M>, KmcError> {
+        KmcFile::open(path.as_ref(), self.validate_exists)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Kmer;
+
+    #[test]
+    fn test_open_options() -> Result<(), KmcError> {
+        let db = KmcOpenOptions::new().random_access().open("./data/test1")?;
+        assert_eq!(db.count_kmer(&Kmer::from("TAAGA")?), 4);
+        let mut db = KmcOpenOptions::default().listing().open("./data/test1")?;
+        assert_eq!(db.to_hashmap().len(), 291);
+        let db = KmcOpenOptions::new()
+            .listing()
+            .random_access()
+            .open("./data/test1.kmc_pre")?;
+        assert_eq!(db.count_str("TAAGA")?, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_options_validate() {
+        let options = KmcOpenOptions::new().listing();
+        assert!(matches!(
+            options.open("./data/missing"),
+            Err(KmcError::MissingFile { .. })
+        ));
+        assert!(matches!(
+            options.validate_exists(false).open("./data/missing"),
+            Err(KmcError::OpenFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_open_failed_reason() {
+        let options = KmcOpenOptions::new().validate_exists(false);
+        match options.open("./data/missing") {
+            Err(KmcError::OpenFailed { reason, .. }) => {
+                assert_eq!(reason, "No such file or directory")
+            }
+            _ => panic!("expected KmcError::OpenFailed"),
+        }
+        let e = options.open("./data/missing").err().unwrap();
+        assert!(
+            e.to_string().ends_with(": No such file or directory"),
+            "{}",
+            e
+        );
+    }
+}
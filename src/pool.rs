@@ -0,0 +1,125 @@
+//! Several handles of the same data base, see [KmcPool].
+use crate::{KmcError, KmcFile, KmcFileRa};
+use std::path::Path;
+use std::sync::{Condvar, Mutex, PoisonError};
+
+/// A fixed number of independent handles of the same data base opened in
+/// random access mode, handed out to one thread at a time each.
+///
+/// A [KmcFileRa] is `Sync` already, so a single handle might be shared, too;
+/// a pool keeps the threads apart from each other's KMC state entirely, at the
+/// cost of loading the data base `n` times.
+/// ```rust
+/// let pool = kmc_rs::KmcPool::open("data/test1", 2)?;
+/// let count = pool.with_handle(|db| db.count_str("TAAGA"))?;
+/// assert_eq!(count, 4);
+/// # Ok::<(), kmc_rs::KmcError>(())
+/// ```
+pub struct KmcPool {
+    idle: Mutex<Vec<KmcFileRa>>,
+    returned: Condvar,
+    size: usize,
+}
+
+impl KmcPool {
+    /// Open the data base `path` `n` times (at least once).
+    pub fn open(path: impl AsRef<Path>, n: usize) -> Result<Self, KmcError> {
+        let handles = (0..n.max(1))
+            .map(|_| KmcFile::open_ra(path.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            size: handles.len(),
+            idle: Mutex::new(handles),
+            returned: Condvar::new(),
+        })
+    }
+
+    /// Number of handles.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Call `f` with a handle no other thread uses meanwhile; blocks until
+    /// one is available.
+    pub fn with_handle<R>(&self, f: impl FnOnce(&KmcFileRa) -> R) -> R {
+        let handle = {
+            let mut idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+            loop {
+                match idle.pop() {
+                    Some(handle) => break handle,
+                    None => {
+                        idle = self
+                            .returned
+                            .wait(idle)
+                            .unwrap_or_else(PoisonError::into_inner)
+                    }
+                }
+            }
+        };
+        let lease = Lease {
+            pool: self,
+            handle: Some(handle),
+        };
+        f(lease.handle.as_ref().expect("returned on drop only"))
+    }
+}
+
+/// Gives the handle back to the pool, even if the caller panics.
+struct Lease<'a> {
+    pool: &'a KmcPool,
+    handle: Option<KmcFileRa>,
+}
+
+impl<'a> Drop for Lease<'a> {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.pool
+                .idle
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(handle);
+            self.pool.returned.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Kmer;
+
+    #[test]
+    fn test_pool() -> Result<(), KmcError> {
+        let pool = KmcPool::open("./data/test1", 3)?;
+        assert_eq!(pool.size(), 3);
+        let mut listing = KmcFile::open_iter("./data/test1")?;
+        let kmers: Vec<Kmer> = listing.iter_kmers().map(|(kmer, _)| kmer).collect();
+        let serial = KmcFile::open_ra("./data/test1")?.count_kmers(&kmers);
+        let pool = &pool;
+        let parallel: Vec<Vec<usize>> = std::thread::scope(|scope| {
+            let workers: Vec<_> = kmers
+                .chunks(20)
+                .map(|chunk| scope.spawn(move || pool.with_handle(|db| db.count_kmers(chunk))))
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+        assert_eq!(parallel.concat(), serial);
+        assert_eq!(pool.idle.lock().unwrap().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pool_errors() {
+        assert!(matches!(
+            KmcPool::open("./data/missing", 2),
+            Err(KmcError::MissingFile { .. })
+        ));
+        let pool = KmcPool::open("./data/test1", 0).unwrap();
+        assert_eq!(pool.size(), 1);
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            pool.with_handle(|_| panic!("oops"))
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(pool.with_handle(|db| db.kmer_length()), 5);
+    }
+}
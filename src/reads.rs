@@ -0,0 +1,157 @@
+//! Reading sequences from FASTA or FASTQ files, see
+//! [KmcFile::count_reads](crate::KmcFile::count_reads).
+use crate::{KmcError, KmcFileRa};
+use std::io::BufRead;
+
+/// Iterator over the records `(name, sequence)` of a FASTA or FASTQ file.
+///
+/// The format is detected per record by its first character (`>` or `@`).
+/// FASTA sequences might span several lines; FASTQ sequences must not.
+pub struct Records<R> {
+    lines: std::io::Lines<R>,
+    next_header: Option<String>,
+}
+
+impl<R: BufRead> Records<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            next_header: None,
+        }
+    }
+
+    fn next_line(&mut self, what: &str) -> Result<String, KmcError> {
+        match self.lines.next() {
+            Some(line) => Ok(line?),
+            None => Err(KmcError::Format(format!("missing {}", what))),
+        }
+    }
+
+    fn read_record(&mut self) -> Result<Option<(String, String)>, KmcError> {
+        let header = match self.next_header.take() {
+            Some(header) => header,
+            None => loop {
+                match self.lines.next() {
+                    None => return Ok(None),
+                    Some(line) => {
+                        let line = line?;
+                        if !line.trim().is_empty() {
+                            break line;
+                        }
+                    }
+                }
+            },
+        };
+        if let Some(name) = header.strip_prefix('>') {
+            let mut seq = String::new();
+            for line in &mut self.lines {
+                let line = line?;
+                if line.starts_with('>') {
+                    self.next_header = Some(line);
+                    break;
+                }
+                seq.push_str(line.trim());
+            }
+            Ok(Some((name.trim().to_string(), seq)))
+        } else if let Some(name) = header.strip_prefix('@') {
+            let seq = self.next_line("FASTQ sequence")?;
+            if !self.next_line("FASTQ separator")?.starts_with('+') {
+                return Err(KmcError::Format(format!(
+                    "expected '+' after sequence of '{}'",
+                    name
+                )));
+            }
+            self.next_line("FASTQ quality")?;
+            Ok(Some((name.trim().to_string(), seq.trim().to_string())))
+        } else {
+            Err(KmcError::Format(format!("unexpected line '{}'", header)))
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = Result<(String, String), KmcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+impl KmcFileRa {
+    /// Count the kmers of all reads in a FASTA or FASTQ file (see [Records]).
+    ///
+    /// Yields the name of every record together with its
+    /// [counts](crate::KmcFile::counts_for_read); lower case symbols are fine and
+    /// kmers containing an `N` have count `0`.
+    /// ```rust
+    /// let db = kmc_rs::KmcFile::open_ra("data/test1")?;
+    /// let fasta = b">read1\nTCTTAAGACG\n" as &[u8];
+    /// for record in db.count_reads(fasta) {
+    ///     let (name, counts) = record?;
+    ///     assert_eq!(name, "read1");
+    ///     assert_eq!(counts, vec![4, 2, 2, 4, 2, 0]);
+    /// }
+    /// # Ok::<(), kmc_rs::KmcError>(())
+    /// ```
+    pub fn count_reads<'a, R: BufRead + 'a>(
+        &'a self,
+        reader: R,
+    ) -> impl Iterator<Item = Result<(String, Vec<usize>), KmcError>> + 'a {
+        Records::new(reader).map(move |record| {
+            let (name, seq) = record?;
+            let counts = self.counts_for_read(&seq)?;
+            Ok((name, counts))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KmcFile;
+
+    #[test]
+    fn test_fasta() -> Result<(), KmcError> {
+        let fasta = b">read1 first\nTCTTA\nAGACG\n\n>read2\ntcttaNGACGAC\n" as &[u8];
+        let records = Records::new(fasta).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            records,
+            vec![
+                ("read1 first".to_string(), "TCTTAAGACG".to_string()),
+                ("read2".to_string(), "tcttaNGACGAC".to_string())
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fastq() -> Result<(), KmcError> {
+        let fastq = b"@r1\nTCTTAAGACG\n+\nIIIIIIIIII\n@r2\nACGT\n+r2\nIIII\n" as &[u8];
+        let records = Records::new(fastq).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1], ("r2".to_string(), "ACGT".to_string()));
+
+        let broken = b"@r1\nTCTTAAGACG\nIIIIIIIIII\n" as &[u8];
+        assert!(matches!(
+            Records::new(broken).next(),
+            Some(Err(KmcError::Format(_)))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_reads() -> Result<(), KmcError> {
+        let io = KmcFile::open_ra("./data/test1")?;
+        let fasta = b">read1\nTCTTAAGACG\n>read2\ntcttaNGACGAC\n>short\nTCT\n" as &[u8];
+        let counts = io.count_reads(fasta).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            counts,
+            vec![
+                ("read1".to_string(), vec![4, 2, 2, 4, 2, 0]),
+                ("read2".to_string(), vec![4, 0, 0, 0, 0, 0, 0, 3]),
+                ("short".to_string(), vec![]),
+            ]
+        );
+        Ok(())
+    }
+}
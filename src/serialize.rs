@@ -0,0 +1,63 @@
+//! [serde] support for [Kmer]: a kmer is serialized as its length `k` and
+//! its [packed bytes](Kmer::as_bytes), so any `k` is supported.
+use crate::Kmer;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Kmer")]
+struct Packed {
+    k: u32,
+    bytes: Vec<u8>,
+}
+
+impl Serialize for Kmer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Packed {
+            k: self.len(),
+            bytes: self.as_bytes(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Kmer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let packed = Packed::deserialize(deserializer)?;
+        Kmer::from_bytes(packed.k, &packed.bytes).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{KmcError, Kmer};
+
+    const SEQS: [&str; 2] = ["TAAGA", "TCAACCTTGGAATCAACCTTGGAATCAACCTTGGAAGTCA"];
+
+    #[test]
+    fn test_json() -> Result<(), KmcError> {
+        for seq in SEQS.iter() {
+            let json = serde_json::to_string(&Kmer::from(seq)?).unwrap();
+            let kmer: Kmer = serde_json::from_str(&json).unwrap();
+            assert_eq!(kmer.to_string(), *seq);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bincode() -> Result<(), KmcError> {
+        for seq in SEQS.iter() {
+            let bytes = bincode::serialize(&Kmer::from(seq)?).unwrap();
+            let kmer: Kmer = bincode::deserialize(&bytes).unwrap();
+            assert_eq!(kmer.to_string(), *seq);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        assert!(serde_json::from_str::<Kmer>(r#"{"k": 5, "bytes": [3]}"#).is_err());
+        assert!(serde_json::from_str::<Kmer>(r#"{"k": 5, "bytes": [3, 8]}"#).is_ok());
+    }
+}
@@ -0,0 +1,84 @@
+//! Helpers shared by the unit tests.
+use crate::writer::split_kmer;
+use crate::{KmcError, Kmer};
+use std::path::{Path, PathBuf};
+
+/// A scratch directory below [std::env::temp_dir], removed again when
+/// dropped (also when a test panics).
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Create a fresh directory `kmc-rs-<name>-<pid>`; `name` has to be
+    /// unique among the tests, since they run in parallel.
+    pub(crate) fn new(name: &str) -> std::io::Result<Self> {
+        let dir = std::env::temp_dir().join(format!("kmc-rs-{}-{}", name, std::process::id()));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+        }
+        std::fs::create_dir_all(&dir)?;
+        Ok(TempDir(dir))
+    }
+
+    pub(crate) fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Write a KMC 2 data base of `k`-mers (both strands) with canonical kmers
+/// sorted within each bin, so the listing follows `bins` rather than the
+/// order of the kmers.
+///
+/// The signature map sends everything to bin 0, so only listing the data
+/// base gives meaningful results.
+pub(crate) fn write_kmc2(path: &Path, k: u32, bins: &[&[(&str, u32)]]) -> Result<(), KmcError> {
+    const SIGNATURE_LEN: u32 = 5;
+    let lut_len = match k % 4 {
+        0 => 4,
+        r => r,
+    };
+    let mut suf = b"KMCS".to_vec();
+    let mut pre = b"KMCP".to_vec();
+    let mut total = 0u64;
+    for bin in bins {
+        let mut lut = vec![0u64; 1 << (2 * lut_len)];
+        for (kmer, count) in bin.iter() {
+            let (prefix, suffix) = split_kmer(&Kmer::from(kmer)?, lut_len);
+            lut[prefix] += 1;
+            suf.extend_from_slice(&suffix);
+            suf.extend_from_slice(&count.to_le_bytes());
+        }
+        for n in lut {
+            pre.extend_from_slice(&total.to_le_bytes());
+            total += n;
+        }
+    }
+    suf.extend_from_slice(b"KMCS");
+    pre.extend_from_slice(&total.to_le_bytes());
+    for _ in 0..(1 << (2 * SIGNATURE_LEN)) + 1 {
+        pre.extend_from_slice(&0u32.to_le_bytes());
+    }
+    let mut header = Vec::with_capacity(64);
+    header.extend_from_slice(&k.to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes()); // mode: counters
+    header.extend_from_slice(&4u32.to_le_bytes()); // counter size
+    header.extend_from_slice(&lut_len.to_le_bytes());
+    header.extend_from_slice(&SIGNATURE_LEN.to_le_bytes());
+    header.extend_from_slice(&1u32.to_le_bytes()); // min_count
+    header.extend_from_slice(&u32::MAX.to_le_bytes()); // max_count
+    header.extend_from_slice(&total.to_le_bytes());
+    header.push(0); // both strands
+    header.resize(60, 0);
+    header.extend_from_slice(&0x200u32.to_le_bytes()); // version: KMC 2
+    pre.extend_from_slice(&header);
+    pre.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    pre.extend_from_slice(b"KMCP");
+    std::fs::write(path.with_extension("kmc_pre"), pre)?;
+    std::fs::write(path.with_extension("kmc_suf"), suf)?;
+    Ok(())
+}
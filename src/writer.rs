@@ -0,0 +1,331 @@
+//! Writing (small) KMC data bases, see [KmcWriter].
+use crate::{db_prefix, KmcError, Kmer};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Create a new KMC data base (in the format of KMC 1) from sorted kmers.
+///
+/// KMC's API can only read data bases, so this is implemented in Rust.
+/// The kmers have to be [added](KmcWriter::add)
+///  * in strictly increasing order (the [Ord] of [Kmer]), and
+///  * in [canonical](Kmer::canonical) form, unless [KmcWriter::single_strand]
+///    was called.
+///
+/// The data base is complete only after [KmcWriter::finish]:
+/// ```rust
+/// # struct Cleanup(std::path::PathBuf);
+/// # impl Drop for Cleanup {
+/// #     fn drop(&mut self) { let _ = std::fs::remove_dir_all(&self.0); }
+/// # }
+/// # let dir = std::env::temp_dir().join(format!("kmc-rs-doc-{}", std::process::id()));
+/// # std::fs::create_dir_all(&dir)?;
+/// # let _cleanup = Cleanup(dir.clone());
+/// let mut writer = kmc_rs::KmcWriter::create(dir.join("small"), 5)?;
+/// writer.add(&kmc_rs::Kmer::from("AAAAC")?, 3)?;
+/// writer.add(&kmc_rs::Kmer::from("TAAGA")?, 4)?;
+/// writer.finish()?;
+///
+/// let db = kmc_rs::KmcFile::open_ra(dir.join("small"))?;
+/// assert_eq!(db.count_str("TAAGA")?, 4);
+/// # Ok::<(), kmc_rs::KmcError>(())
+/// ```
+pub struct KmcWriter {
+    prefix: String,
+    k: u32,
+    lut_len: u32,
+    both_strands: bool,
+    counter_size: u32,
+    min_count: u32,
+    max_count: u32,
+    suffixes: BufWriter<File>,
+    lut: Vec<u64>,
+    last: Option<Kmer>,
+}
+
+impl KmcWriter {
+    /// Start a new data base of `k`-mers at `path` (without the suffixes
+    /// `.kmc_pre` and `.kmc_suf`).
+    pub fn create(path: impl AsRef<Path>, k: u32) -> Result<Self, KmcError> {
+        if k == 0 {
+            return Err(KmcError::InvalidKmer("k must be positive".to_string()));
+        }
+        let prefix = db_prefix(path.as_ref())?.to_string();
+        let mut suffixes = BufWriter::new(File::create(format!("{}.kmc_suf", prefix))?);
+        suffixes.write_all(b"KMCS")?;
+        // the suffixes need to consist of whole bytes
+        let lut_len = match k % 4 {
+            0 => 4,
+            r => r,
+        };
+        Ok(Self {
+            prefix,
+            k,
+            lut_len,
+            both_strands: true,
+            counter_size: 4,
+            min_count: 1,
+            max_count: u32::MAX,
+            suffixes,
+            lut: vec![0; 1 << (2 * lut_len)],
+            last: None,
+        })
+    }
+
+    /// Mark the data base as counting a single strand, so the kmers need not be
+    /// canonical (like `kmc -b`).
+    pub fn single_strand(mut self) -> Self {
+        self.both_strands = false;
+        self
+    }
+
+    /// Store every count in `bytes` bytes (like `kmc -cs`) rather than four.
+    ///
+    /// # Panics
+    /// If `bytes` is not within `1..=4`.
+    pub fn counter_size(mut self, bytes: u32) -> Self {
+        assert!(
+            (1..=4).contains(&bytes),
+            "counter size must be within 1..=4"
+        );
+        self.counter_size = bytes;
+        self
+    }
+
+    /// Record in the header that only kmers with counts within `min..=max`
+    /// were kept (like `kmc -ci -cx`); the default is `1..=u32::MAX`.
+    pub fn count_range(mut self, min: u32, max: u32) -> Self {
+        self.min_count = min;
+        self.max_count = max;
+        self
+    }
+
+    /// The largest count that fits into the counter size.
+    pub fn max_counter(&self) -> u32 {
+        u32::MAX >> (8 * (4 - self.counter_size))
+    }
+
+    /// Append `kmer` with its `count`, which must be positive, within the
+    /// [count range](KmcWriter::count_range) and at most [KmcWriter::max_counter].
+    pub fn add(&mut self, kmer: &Kmer, count: u32) -> Result<(), KmcError> {
+        if kmer.len() != self.k {
+            return Err(KmcError::LengthMismatch {
+                expected: self.k,
+                got: kmer.len(),
+            });
+        }
+        if count == 0 {
+            return Err(KmcError::Format(format!("count of {} is zero", kmer)));
+        }
+        if count < self.min_count || count > self.max_count.min(self.max_counter()) {
+            return Err(KmcError::Format(format!(
+                "count {} of {} is not within {}..={}",
+                count,
+                kmer,
+                self.min_count,
+                self.max_count.min(self.max_counter())
+            )));
+        }
+        if self.both_strands && !kmer.is_canonical() {
+            return Err(KmcError::InvalidKmer(format!("{} is not canonical", kmer)));
+        }
+        if let Some(last) = &self.last {
+            if last >= kmer {
+                return Err(KmcError::Format(format!(
+                    "{} is not added after {}",
+                    kmer, last
+                )));
+            }
+        }
+        let (prefix, suffix) = split_kmer(kmer, self.lut_len);
+        self.lut[prefix] += 1;
+        self.suffixes.write_all(&suffix)?;
+        self.suffixes
+            .write_all(&count.to_le_bytes()[..self.counter_size as usize])?;
+        self.last = Some(kmer.clone());
+        Ok(())
+    }
+
+    /// Write the remaining parts of the data base and close its files.
+    pub fn finish(mut self) -> Result<(), KmcError> {
+        self.suffixes.write_all(b"KMCS")?;
+        self.suffixes.flush()?;
+
+        let mut pre = BufWriter::new(File::create(format!("{}.kmc_pre", self.prefix))?);
+        pre.write_all(b"KMCP")?;
+        // the look up table stores the index of the first kmer of every prefix
+        let mut total = 0u64;
+        for n in &self.lut {
+            pre.write_all(&total.to_le_bytes())?;
+            total += n;
+        }
+        let mut header = Vec::with_capacity(64);
+        header.extend_from_slice(&self.k.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes()); // mode: counters
+        header.extend_from_slice(&self.counter_size.to_le_bytes());
+        header.extend_from_slice(&self.lut_len.to_le_bytes());
+        header.extend_from_slice(&self.min_count.to_le_bytes());
+        header.extend_from_slice(&self.max_count.to_le_bytes());
+        header.extend_from_slice(&total.to_le_bytes());
+        header.push(if self.both_strands { 0 } else { 1 });
+        header.resize(60, 0);
+        header.extend_from_slice(&0u32.to_le_bytes()); // version: KMC 1
+        pre.write_all(&header)?;
+        pre.write_all(&(header.len() as u32).to_le_bytes())?;
+        pre.write_all(b"KMCP")?;
+        pre.flush()?;
+        Ok(())
+    }
+}
+
+/// Split `kmer` like KMC 1 does into the index of its first `lut_len` symbols
+/// in the look up table and the remaining symbols packed into bytes
+/// (which requires `kmer.len() - lut_len` to be a multiple of four).
+pub(crate) fn split_kmer(kmer: &Kmer, lut_len: u32) -> (usize, Vec<u8>) {
+    let mut codes = kmer.codes();
+    let prefix = codes
+        .by_ref()
+        .take(lut_len as usize)
+        .fold(0, |p, c| (p << 2) | c as usize);
+    let mut suffix = Vec::with_capacity((kmer.len().saturating_sub(lut_len) / 4) as usize);
+    let mut byte = 0;
+    for (i, c) in codes.enumerate() {
+        byte = (byte << 2) | c;
+        if i % 4 == 3 {
+            suffix.push(byte);
+            byte = 0;
+        }
+    }
+    (prefix, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use crate::KmcFile;
+
+    #[test]
+    fn test_write_read() -> Result<(), KmcError> {
+        let dir = TempDir::new("write-read")?;
+        let kmers = [("AAAAC", 3), ("ACGTA", 1), ("CAAAA", 70000), ("TAAGA", 4)];
+        let mut writer = KmcWriter::create(dir.join("small"), 5)?;
+        for (seq, count) in &kmers {
+            writer.add(&Kmer::from(seq)?, *count)?;
+        }
+        writer.finish()?;
+
+        let io = KmcFile::open_ra(dir.join("small"))?;
+        assert_eq!(io.kmer_length(), 5);
+        assert!(io.both_strands());
+        assert_eq!(io.total_kmers(), 4);
+        for (seq, count) in &kmers {
+            assert_eq!(io.count_str(seq)?, *count as usize);
+        }
+        assert_eq!(io.count_str("AAAAA")?, 0);
+        let mut io = KmcFile::open_iter(dir.join("small"))?;
+        let listed: Vec<(String, usize)> = io.iter_strings().collect();
+        assert_eq!(
+            listed,
+            kmers
+                .iter()
+                .map(|&(seq, count)| (seq.to_string(), count as usize))
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_test1() -> Result<(), KmcError> {
+        let dir = TempDir::new("copy-test1")?;
+        let mut io = KmcFile::open_iter("./data/test1")?;
+        let mut writer = KmcWriter::create(dir.join("copy"), 5)?;
+        for (kmer, count) in io.iter_kmers() {
+            writer.add(&kmer, count as u32)?;
+        }
+        writer.finish()?;
+        assert!(io.restart());
+        let copy = KmcFile::open_iter(dir.join("copy"))?.to_hashmap();
+        assert_eq!(copy, io.to_hashmap());
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_lengths() -> Result<(), KmcError> {
+        let dir = TempDir::new("write-lengths")?;
+        for &k in &[1, 3, 4, 8, 33, 70] {
+            let kmer = Kmer::from(&"ACGT".repeat(20)[..k])?.canonical();
+            let mut writer = KmcWriter::create(dir.join("k"), k as u32)?;
+            writer.add(&kmer, 2)?;
+            writer.finish()?;
+            let io = KmcFile::open_ra(dir.join("k"))?;
+            assert_eq!(io.kmer_length(), k as u32);
+            assert_eq!(io.count_kmer(&kmer), 2);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_errors() -> Result<(), KmcError> {
+        let dir = TempDir::new("write-errors")?;
+        let mut writer = KmcWriter::create(dir.join("errors"), 5)?;
+        writer.add(&Kmer::from("CAAAA")?, 1)?;
+        assert!(matches!(
+            writer.add(&Kmer::from("AAAAC")?, 1),
+            Err(KmcError::Format(_))
+        ));
+        assert!(matches!(
+            writer.add(&Kmer::from("CAAAA")?, 1),
+            Err(KmcError::Format(_))
+        ));
+        assert!(matches!(
+            writer.add(&Kmer::from("TTTTT")?, 1),
+            Err(KmcError::InvalidKmer(_))
+        ));
+        assert!(matches!(
+            writer.add(&Kmer::from("CAAAAA")?, 1),
+            Err(KmcError::LengthMismatch { .. })
+        ));
+        assert!(matches!(
+            writer.add(&Kmer::from("GAAAA")?, 0),
+            Err(KmcError::Format(_))
+        ));
+        let mut writer = writer.single_strand();
+        writer.add(&Kmer::from("TTTTT")?, 1)?;
+        writer.finish()?;
+        let io = KmcFile::open_ra(dir.join("errors"))?;
+        assert!(!io.both_strands());
+        assert_eq!(io.count_str("TTTTT")?, 1);
+        assert_eq!(io.count_str("CAAAA")?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_header() -> Result<(), KmcError> {
+        let dir = TempDir::new("write-header")?;
+        let mut writer = KmcWriter::create(dir.join("small"), 5)?
+            .counter_size(1)
+            .count_range(2, 1000);
+        assert_eq!(writer.max_counter(), 255);
+        for &count in &[1, 256] {
+            assert!(matches!(
+                writer.add(&Kmer::from("AAAAC")?, count),
+                Err(KmcError::Format(_))
+            ));
+        }
+        writer.add(&Kmer::from("AAAAC")?, 2)?;
+        writer.add(&Kmer::from("TAAGA")?, 255)?;
+        writer.finish()?;
+        let io = KmcFile::open_ra(dir.join("small"))?;
+        assert_eq!(
+            (io.counter_size(), io.min_count(), io.max_count()),
+            (1, 2, 1000)
+        );
+        assert_eq!(io.count_str("TAAGA")?, 255);
+        assert_eq!(
+            std::fs::metadata(dir.join("small.kmc_suf"))?.len(),
+            4 + 2 * 2 + 4
+        );
+        Ok(())
+    }
+}